@@ -0,0 +1,51 @@
+#!/usr/bin/env bash
+# 按 feature 组合检查 wd_passport：clippy 无警告且测试通过
+# 用法：ci/features.sh [额外的 cargo 参数]
+set -euo pipefail
+cd "$(dirname "$0")/.."
+
+combos=(
+    ""
+    "verify"
+    "verify,encrypt"
+    "sign"
+    "sign,encrypt"
+    "sign,verify,encrypt"
+    "global"
+    "global,encrypt"
+    "ffi"
+    "embedded"
+    "verify,embedded"
+    "sign,verify,encrypt,global,ffi,embedded"
+)
+
+for features in "${combos[@]}"; do
+    echo "==> wd_passport features: [${features}]"
+    args=(-p wd_passport --no-default-features)
+    if [ -n "${features}" ]; then
+        args+=(--features "${features}")
+    fi
+    cargo clippy "${args[@]}" --all-targets "$@" -- -D warnings
+    cargo test "${args[@]}" "$@"
+done
+
+# 不开启 ffi 时不能导出任何 C 符号
+echo "==> no C symbols without ffi"
+cargo build -p wd_passport "$@"
+profile=debug
+for arg in "$@"; do
+    if [ "${arg}" = "--release" ]; then
+        profile=release
+    fi
+done
+rlib="${CARGO_TARGET_DIR:-target}/${profile}/libwd_passport.rlib"
+if nm -g --defined-only "${rlib}" 2>/dev/null | grep -E ' T (passport_[a-z0-9_]+|sign|verify|encrypt|decrypt)$'; then
+    echo "C symbols exported without the ffi feature" >&2
+    exit 1
+fi
+
+echo "==> wd_passport_core features"
+cargo clippy -p wd_passport_core --all-targets "$@" -- -D warnings
+cargo test -p wd_passport_core "$@"
+cargo clippy -p wd_passport_core --all-targets --features alloc "$@" -- -D warnings
+cargo test -p wd_passport_core --features alloc "$@"
@@ -0,0 +1,109 @@
+use crate::keyring::parse_time;
+use clap::Args;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use wd_passport::{decode, encode};
+use wd_passport::{PassportEntity, PassportError};
+
+///进程退出码，脚本可以据此区分失败原因
+pub const EXIT_ERROR: u8 = 1;
+pub const EXIT_OUT_OF_RANGE: u8 = 3;
+pub const EXIT_BAD_SIGNATURE: u8 = 4;
+
+///按 PassportError 选择退出码
+pub fn exit_code(e: &anyhow::Error) -> u8 {
+    match e.downcast_ref::<PassportError>() {
+        Some(PassportError::OutOfRange(_)) => EXIT_OUT_OF_RANGE,
+        Some(PassportError::BadSignature) => EXIT_BAD_SIGNATURE,
+        _ => EXIT_ERROR,
+    }
+}
+
+///输入输出与时间戳参数
+#[derive(Args, Debug, Clone)]
+pub struct DataArgs {
+    /// Read the data from this file instead of stdin ("-" is stdin)
+    #[arg(long = "in", value_name = "PATH")]
+    pub input: Option<PathBuf>,
+    /// Window time: unix seconds, RFC 3339, "YYYY-MM-DD HH:MM:SS" or "YYYY-MM-DD"; defaults to now
+    #[arg(long, value_name = "TIME")]
+    pub timestamp: Option<String>,
+}
+
+impl DataArgs {
+    pub fn read(&self, stdin: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
+        let mut buf = vec![];
+        match self.input.as_ref() {
+            Some(path) if path.as_os_str() != "-" => {
+                buf = std::fs::read(path)
+                    .map_err(|e| anyhow::anyhow!("read {} error:{}", path.display(), e))?;
+            }
+            _ => {
+                stdin.read_to_end(&mut buf)?;
+            }
+        }
+        Ok(buf)
+    }
+    pub fn timestamp(&self) -> anyhow::Result<i64> {
+        match self.timestamp.as_ref() {
+            Some(t) => Ok(parse_time(t)?.timestamp()),
+            None => Ok(wd_passport::Utc::now().timestamp()),
+        }
+    }
+}
+
+//base64url 文本，忽略首尾空白
+fn decode_text(buf: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let text = std::str::from_utf8(buf)?;
+    decode(text.trim())
+}
+
+pub fn sign(
+    entity: &PassportEntity,
+    args: &DataArgs,
+    stdin: &mut dyn Read,
+    stdout: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let t = args.timestamp()?;
+    let sign = entity.sign_sha256(args.read(stdin)?, t)?;
+    writeln!(stdout, "{}", encode(sign)?)?;
+    Ok(())
+}
+
+pub fn verify(
+    entity: &PassportEntity,
+    args: &DataArgs,
+    signature: &[u8],
+    stdin: &mut dyn Read,
+    stdout: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let t = args.timestamp()?;
+    let signature = match decode_text(signature) {
+        Ok(o) => o,
+        Err(_) => return Err(anyhow::Error::from(PassportError::BadSignature)),
+    };
+    let data = args.read(stdin)?;
+    entity.verify_sha256(data.as_slice(), signature.as_slice(), t)?;
+    writeln!(stdout, "ok")?;
+    Ok(())
+}
+
+pub fn encrypt(
+    entity: &PassportEntity,
+    args: &DataArgs,
+    stdin: &mut dyn Read,
+) -> anyhow::Result<Vec<u8>> {
+    let t = args.timestamp()?;
+    let buf = entity.encrypt(args.read(stdin)?, t)?;
+    Ok((encode(buf)? + "\n").into_bytes())
+}
+
+pub fn decrypt(
+    entity: &PassportEntity,
+    args: &DataArgs,
+    stdin: &mut dyn Read,
+) -> anyhow::Result<Vec<u8>> {
+    let t = args.timestamp()?;
+    let buf = decode_text(args.read(stdin)?.as_slice())?;
+    entity.decrypt(buf, t)
+}
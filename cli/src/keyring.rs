@@ -0,0 +1,156 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use clap::Args;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use wd_passport::{KeyringSnapshot, PassportEntity};
+use zeroize::Zeroizing;
+
+///证书来源：key 文件 + 时间表重新推导，或者读取 snapshot 导出的快照
+#[derive(Args, Debug, Clone)]
+pub struct KeyringArgs {
+    /// File holding the passport secret; a single trailing newline is ignored
+    #[arg(long, value_name = "PATH", conflicts_with = "snapshot", requires_all = ["start", "end", "interval"])]
+    pub secret_file: Option<PathBuf>,
+    /// First second of the schedule: unix seconds, RFC 3339, "YYYY-MM-DD HH:MM:SS" or "YYYY-MM-DD" (UTC)
+    #[arg(long, value_name = "TIME", requires = "secret_file")]
+    pub start: Option<String>,
+    /// Last second of the schedule, same formats as --start
+    #[arg(long, value_name = "TIME", requires = "secret_file")]
+    pub end: Option<String>,
+    /// Window length: seconds, or a number followed by s, m, h or d
+    #[arg(long, value_name = "DURATION", requires = "secret_file")]
+    pub interval: Option<String>,
+    /// RSA modulus size of every window key
+    #[arg(long, default_value_t = 2048)]
+    pub bits: usize,
+    /// Keyring snapshot written by `passport snapshot`
+    #[arg(long, value_name = "PATH")]
+    pub snapshot: Option<PathBuf>,
+    /// File holding the password of the private keys in a snapshot
+    #[arg(long, value_name = "PATH")]
+    pub password_file: Option<PathBuf>,
+}
+
+impl KeyringArgs {
+    pub fn load(&self) -> anyhow::Result<PassportEntity> {
+        if let Some(path) = self.snapshot.as_ref() {
+            let json = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("read snapshot {} error:{}", path.display(), e))?;
+            let password = self.password()?;
+            return KeyringSnapshot::from_json(json)?.restore(password.as_deref().map(|p| &p[..]));
+        }
+        let (path, start, end, interval) = match (
+            self.secret_file.as_ref(),
+            self.start.as_ref(),
+            self.end.as_ref(),
+            self.interval.as_ref(),
+        ) {
+            (Some(p), Some(s), Some(e), Some(i)) => (p, s, e, i),
+            _ => {
+                anyhow::bail!(
+                "either --snapshot or --secret-file with --start, --end and --interval is required"
+            )
+            }
+        };
+        let secret = read_secret(path)?;
+        let range = (parse_time(start)?, parse_time(end)?);
+        PassportEntity::new(
+            secret.as_slice(),
+            range,
+            parse_interval(interval)?,
+            self.bits,
+        )
+    }
+    pub fn password(&self) -> anyhow::Result<Option<Zeroizing<Vec<u8>>>> {
+        match self.password_file.as_ref() {
+            Some(path) => Ok(Some(read_secret(path)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+//读取 key 或密码文件，去掉编辑器或 echo 追加的一个换行
+pub fn read_secret(path: &Path) -> anyhow::Result<Zeroizing<Vec<u8>>> {
+    let buf =
+        std::fs::read(path).map_err(|e| anyhow::anyhow!("read {} error:{}", path.display(), e))?;
+    let mut buf = Zeroizing::new(buf);
+    if buf.ends_with(b"\n") {
+        buf.pop();
+        if buf.ends_with(b"\r") {
+            buf.pop();
+        }
+    }
+    if buf.is_empty() {
+        anyhow::bail!("{} is empty", path.display());
+    }
+    Ok(buf)
+}
+
+pub fn parse_time(s: &str) -> anyhow::Result<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(sec) = s.parse::<i64>() {
+        return match DateTime::from_timestamp(sec, 0) {
+            Some(t) => Ok(t),
+            None => Err(anyhow::anyhow!("timestamp[{}] out of range", sec)),
+        };
+    }
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Ok(t.with_timezone(&Utc));
+    }
+    if let Ok(t) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
+        return Ok(t.and_utc());
+    }
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(d.and_time(Default::default()).and_utc());
+    }
+    Err(anyhow::anyhow!("invalid time {:?}", s))
+}
+
+pub fn parse_interval(s: &str) -> anyhow::Result<Duration> {
+    let s = s.trim();
+    let (num, unit) = match s.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => (&s[..i], c),
+        _ => (s, 's'),
+    };
+    let num: u64 = num
+        .parse()
+        .map_err(|e| anyhow::anyhow!("invalid interval {:?}:{}", s, e))?;
+    let sec = match unit {
+        's' => Some(num),
+        'm' => num.checked_mul(60),
+        'h' => num.checked_mul(60 * 60),
+        'd' => num.checked_mul(60 * 60 * 24),
+        _ => anyhow::bail!("invalid interval unit {:?}", unit),
+    };
+    match sec {
+        Some(sec) if sec > 0 => Ok(Duration::from_secs(sec)),
+        _ => Err(anyhow::anyhow!("invalid interval {:?}", s)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_interval, parse_time};
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse() {
+        let t = 1675641600;
+        for s in [
+            "1675641600",
+            "2023-02-06T00:00:00Z",
+            "2023-02-06T08:00:00+08:00",
+            "2023-02-06 00:00:00",
+            "2023-02-06",
+        ] {
+            assert_eq!(parse_time(s).expect(s).timestamp(), t, "{}", s);
+        }
+        assert!(parse_time("next week").is_err());
+        assert_eq!(parse_interval("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_interval("30d").unwrap(), Duration::from_secs(2592000));
+        assert_eq!(parse_interval("2h").unwrap(), Duration::from_secs(7200));
+        assert!(parse_interval("0").is_err());
+        assert!(parse_interval("3w").is_err());
+        assert!(parse_interval("d").is_err());
+    }
+}
@@ -0,0 +1,724 @@
+mod data;
+mod keyring;
+#[cfg(feature = "server")]
+mod server;
+
+use crate::data::DataArgs;
+use crate::keyring::{parse_time, KeyringArgs};
+use clap::{Parser, Subcommand, ValueEnum};
+use rsa::Pkcs1v15Encrypt;
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use wd_passport::{window_start, KeyringSnapshot, PassportEntity, PublicKeyFormat, RsaEntity};
+
+#[derive(Parser, Debug)]
+#[command(name = "passport", version, about = "Manage wd_passport keyrings")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List every window with its validity and public key fingerprint
+    List {
+        #[command(flatten)]
+        keyring: KeyringArgs,
+        /// Print JSON instead of one line per window
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export the public keys of every window
+    Export {
+        #[command(flatten)]
+        keyring: KeyringArgs,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Pem)]
+        format: ExportFormat,
+        /// Write to this file instead of stdout
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+    /// Export a JSON snapshot that can be loaded with --snapshot without the secret
+    Snapshot {
+        #[command(flatten)]
+        keyring: KeyringArgs,
+        /// Include the private keys, encrypted with the password from --password-file
+        #[arg(long, requires = "password_file")]
+        include_private: bool,
+        /// Write to this file instead of stdout
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+    /// Check that a public key (PKCS#1 or SPKI, PEM or DER) belongs to the keyring
+    Check {
+        #[command(flatten)]
+        keyring: KeyringArgs,
+        #[arg(long, value_name = "PATH")]
+        public_key: PathBuf,
+        /// Also require the key to be the one of the window containing this time
+        #[arg(long, value_name = "TIME")]
+        timestamp: Option<String>,
+    },
+    /// Sign data with the key of the window containing --timestamp, print the base64url signature
+    Sign {
+        #[command(flatten)]
+        keyring: KeyringArgs,
+        #[command(flatten)]
+        data: DataArgs,
+    },
+    /// Verify a base64url signature; exits 3 when --timestamp is outside every window, 4 on a bad signature
+    Verify {
+        #[command(flatten)]
+        keyring: KeyringArgs,
+        #[command(flatten)]
+        data: DataArgs,
+        /// The base64url signature
+        #[arg(
+            long,
+            value_name = "BASE64URL",
+            conflicts_with = "signature_file",
+            required_unless_present = "signature_file"
+        )]
+        signature: Option<String>,
+        /// Read the base64url signature from this file
+        #[arg(long, value_name = "PATH")]
+        signature_file: Option<PathBuf>,
+    },
+    /// Encrypt data for the window containing --timestamp, print base64url ciphertext
+    Encrypt {
+        #[command(flatten)]
+        keyring: KeyringArgs,
+        #[command(flatten)]
+        data: DataArgs,
+        /// Write to this file instead of stdout
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+    /// Serve sign, verify, encrypt, decrypt and key discovery over HTTP
+    #[cfg(feature = "server")]
+    Serve {
+        #[command(flatten)]
+        keyring: KeyringArgs,
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+        listen: String,
+        /// API keys accepted by /sign and /decrypt, one per line; without it both are refused
+        #[arg(long, value_name = "PATH")]
+        api_key_file: Option<PathBuf>,
+        #[arg(long, default_value_t = 4)]
+        threads: usize,
+    },
+    /// Hold the keyring in a signing agent listening on a Unix socket
+    #[cfg(unix)]
+    Agent {
+        #[command(flatten)]
+        keyring: KeyringArgs,
+        #[arg(long, value_name = "PATH")]
+        socket: PathBuf,
+        /// Allow a peer uid, optionally limited to some of sign,decrypt,verify,encrypt (e.g. 1000:sign,verify);
+        /// defaults to the current user with every operation
+        #[arg(long, value_name = "UID[:OPS]")]
+        allow: Vec<String>,
+        /// Close new connections beyond this many concurrent ones
+        #[arg(long, default_value_t = wd_passport::DEFAULT_MAX_CONNECTIONS)]
+        max_connections: usize,
+        /// Close connections idle for this many seconds, 0 to never close them
+        #[arg(long, value_name = "SECONDS", default_value_t = wd_passport::DEFAULT_TIMEOUT.as_secs())]
+        timeout: u64,
+    },
+    /// Decrypt base64url ciphertext produced by `passport encrypt`
+    Decrypt {
+        #[command(flatten)]
+        keyring: KeyringArgs,
+        #[command(flatten)]
+        data: DataArgs,
+        /// Write to this file instead of stdout
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    /// PKCS#1 PEM, one block per window
+    Pem,
+    /// SubjectPublicKeyInfo PEM, one block per window
+    Spki,
+    /// OpenSSH authorized_keys lines
+    Openssh,
+    /// JSON Web Key Set
+    Jwks,
+    /// Rust source with a static key table for wd_passport_core
+    Rust,
+}
+
+#[derive(Serialize)]
+struct WindowInfo {
+    window: i64,
+    not_before: String,
+    not_after: String,
+    key_id: String,
+    fingerprint: String,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(
+        cli,
+        &mut std::io::stdin().lock(),
+        &mut std::io::stdout().lock(),
+    ) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("passport: {:#}", e);
+            ExitCode::from(data::exit_code(&e))
+        }
+    }
+}
+
+fn run(cli: Cli, stdin: &mut dyn Read, stdout: &mut dyn Write) -> anyhow::Result<()> {
+    match cli.command {
+        Command::List { keyring, json } => list(&keyring.load()?, json, stdout),
+        Command::Export {
+            keyring,
+            format,
+            out,
+        } => {
+            let buf = export(&keyring.load()?, format)?;
+            write_out(out.as_deref(), buf.as_slice(), stdout)
+        }
+        Command::Snapshot {
+            keyring,
+            include_private,
+            out,
+        } => {
+            let entity = keyring.load()?;
+            let password = match include_private {
+                true => keyring.password()?,
+                false => None,
+            };
+            let snapshot =
+                KeyringSnapshot::from_entity(&entity, password.as_deref().map(|p| &p[..]))?;
+            let json = snapshot.to_json()? + "\n";
+            write_out(out.as_deref(), json.as_bytes(), stdout)
+        }
+        Command::Check {
+            keyring,
+            public_key,
+            timestamp,
+        } => check(&keyring.load()?, &public_key, timestamp.as_deref(), stdout),
+        Command::Sign { keyring, data } => data::sign(&keyring.load()?, &data, stdin, stdout),
+        Command::Verify {
+            keyring,
+            data,
+            signature,
+            signature_file,
+        } => {
+            let signature = match (signature, signature_file) {
+                (Some(s), _) => s.into_bytes(),
+                (None, Some(path)) => std::fs::read(&path)
+                    .map_err(|e| anyhow::anyhow!("read {} error:{}", path.display(), e))?,
+                (None, None) => anyhow::bail!("--signature is required"),
+            };
+            data::verify(&keyring.load()?, &data, signature.as_slice(), stdin, stdout)
+        }
+        Command::Encrypt { keyring, data, out } => {
+            let buf = data::encrypt(&keyring.load()?, &data, stdin)?;
+            write_out(out.as_deref(), buf.as_slice(), stdout)
+        }
+        Command::Decrypt { keyring, data, out } => {
+            let buf = data::decrypt(&keyring.load()?, &data, stdin)?;
+            write_out(out.as_deref(), buf.as_slice(), stdout)
+        }
+        #[cfg(unix)]
+        Command::Agent {
+            keyring,
+            socket,
+            allow,
+            max_connections,
+            timeout,
+        } => {
+            let timeout = match timeout {
+                0 => None,
+                t => Some(std::time::Duration::from_secs(t)),
+            };
+            let agent = std::sync::Arc::new(
+                wd_passport::PassportAgent::new(keyring.load()?, agent_policy(allow.as_slice())?)
+                    .set_max_connections(max_connections)
+                    .set_timeout(timeout),
+            );
+            let listener = agent.bind(&socket)?;
+            eprintln!("passport: agent listening on {}", socket.display());
+            agent.serve(listener)
+        }
+        #[cfg(feature = "server")]
+        Command::Serve {
+            keyring,
+            listen,
+            api_key_file,
+            threads,
+        } => {
+            let api_keys = match api_key_file {
+                Some(path) => server::read_api_keys(&path)?,
+                None => vec![],
+            };
+            let service = server::Service::new(keyring.load()?, api_keys);
+            let server = std::sync::Arc::new(server::HttpServer::bind(listen.as_str(), service)?);
+            if let Some(addr) = server.local_addr() {
+                eprintln!("passport: listening on http://{}", addr);
+            }
+            server.run(threads);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(unix)]
+fn agent_policy(allow: &[String]) -> anyhow::Result<wd_passport::AgentPolicy> {
+    use wd_passport::{AgentOp, AgentPolicy};
+    let mut policy = AgentPolicy::new();
+    if allow.is_empty() {
+        return Ok(policy.allow_current_user());
+    }
+    for rule in allow {
+        let (uid, ops) = match rule.split_once(':') {
+            Some((uid, ops)) => (uid, Some(ops)),
+            None => (rule.as_str(), None),
+        };
+        let uid: u32 = uid
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid uid in --allow {:?}:{}", rule, e))?;
+        let ops = match ops {
+            None => AgentOp::ALL.to_vec(),
+            Some(ops) => {
+                let mut list = vec![];
+                for op in ops.split(',') {
+                    match AgentOp::from_name(op.trim()) {
+                        Some(op) => list.push(op),
+                        None => anyhow::bail!("unknown operation {:?}", op),
+                    }
+                }
+                list
+            }
+        };
+        policy = policy.allow(uid, ops.as_slice());
+    }
+    Ok(policy)
+}
+
+fn window_info(entity: &PassportEntity) -> anyhow::Result<Vec<WindowInfo>> {
+    let interval = entity.upgrade_cycle().as_secs() as i64;
+    let mut res = vec![];
+    for fp in entity.to_public_fingerprint() {
+        res.push(WindowInfo {
+            window: fp.window,
+            not_before: rfc3339(fp.window),
+            not_after: rfc3339(fp.window + interval - 1),
+            key_id: fp.key_id,
+            fingerprint: fp.fingerprint,
+        });
+    }
+    Ok(res)
+}
+
+fn rfc3339(sec: i64) -> String {
+    match chrono::DateTime::from_timestamp(sec, 0) {
+        Some(t) => t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        None => sec.to_string(),
+    }
+}
+
+fn list(entity: &PassportEntity, json: bool, stdout: &mut dyn Write) -> anyhow::Result<()> {
+    let windows = window_info(entity)?;
+    if json {
+        writeln!(stdout, "{}", serde_json::to_string_pretty(&windows)?)?;
+        return Ok(());
+    }
+    for w in windows {
+        writeln!(
+            stdout,
+            "{}\t{}\t{}\t{}",
+            w.window, w.not_before, w.not_after, w.fingerprint
+        )?;
+    }
+    Ok(())
+}
+
+fn export(entity: &PassportEntity, format: ExportFormat) -> anyhow::Result<Vec<u8>> {
+    let format = match format {
+        ExportFormat::Jwks => return Ok((entity.to_jwks()?.to_json()? + "\n").into_bytes()),
+        ExportFormat::Rust => return rust_table(entity),
+        ExportFormat::Pem => PublicKeyFormat::Pkcs1Pem,
+        ExportFormat::Spki => PublicKeyFormat::SpkiPem,
+        ExportFormat::Openssh => PublicKeyFormat::OpenSsh,
+    };
+    let fingerprints = entity.to_public_fingerprint();
+    let mut buf = vec![];
+    for ((window, key), fp) in entity
+        .export_public_keys(format)?
+        .into_iter()
+        .zip(fingerprints)
+    {
+        //PEM 块之前的文字会被 openssl 等工具忽略，用来标注窗口
+        if format != PublicKeyFormat::OpenSsh {
+            writeln!(buf, "# window {} {}", window, fp.fingerprint)?;
+        }
+        buf.extend_from_slice(key.as_slice());
+        if !buf.ends_with(b"\n") {
+            buf.push(b'\n');
+        }
+    }
+    Ok(buf)
+}
+
+//wd_passport_core 的静态公钥表，每个窗口一个 pkcs1 der 公钥
+fn rust_table(entity: &PassportEntity) -> anyhow::Result<Vec<u8>> {
+    let keys = entity.export_public_keys(PublicKeyFormat::Pkcs1Der)?;
+    let mut buf = vec![];
+    writeln!(buf, "// passport export --format rust")?;
+    writeln!(
+        buf,
+        "pub const INTERVAL_SEC: u64 = {};",
+        entity.upgrade_cycle().as_secs()
+    )?;
+    writeln!(
+        buf,
+        "pub static WINDOW_KEYS: [wd_passport_core::WindowKey; {}] = [",
+        keys.len()
+    )?;
+    for (window, der) in keys {
+        writeln!(buf, "    wd_passport_core::WindowKey::new(")?;
+        writeln!(buf, "        {},", window)?;
+        writeln!(buf, "        &[")?;
+        for chunk in der.chunks(14) {
+            let line: Vec<String> = chunk.iter().map(|b| format!("0x{:02x},", b)).collect();
+            writeln!(buf, "            {}", line.join(" "))?;
+        }
+        writeln!(buf, "        ],")?;
+        writeln!(buf, "    ),")?;
+    }
+    writeln!(buf, "];")?;
+    Ok(buf)
+}
+
+fn check(
+    entity: &PassportEntity,
+    public_key: &Path,
+    timestamp: Option<&str>,
+    stdout: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let buf = std::fs::read(public_key)
+        .map_err(|e| anyhow::anyhow!("read {} error:{}", public_key.display(), e))?;
+    let key = match std::str::from_utf8(buf.as_slice()) {
+        Ok(pem) if pem.contains("-----BEGIN") => RsaEntity::from_public_pem(pem, Pkcs1v15Encrypt)?,
+        _ => RsaEntity::from_public_der(buf.as_slice(), Pkcs1v15Encrypt)?,
+    };
+    let fingerprint = key.fingerprint()?;
+    let window = match entity.window_by_fingerprint(fingerprint.as_str()) {
+        Some(w) => w,
+        None => anyhow::bail!("public key {} is not in the keyring", fingerprint),
+    };
+    if let Some(t) = timestamp {
+        let t = parse_time(t)?.timestamp();
+        let interval = entity.upgrade_cycle().as_secs() as i64;
+        if window_start(t, interval) != window {
+            anyhow::bail!(
+                "public key {} belongs to window {}, not to the window of {}",
+                fingerprint,
+                window,
+                t
+            );
+        }
+    }
+    writeln!(stdout, "ok\t{}\t{}", window, fingerprint)?;
+    Ok(())
+}
+
+fn write_out(out: Option<&Path>, buf: &[u8], stdout: &mut dyn Write) -> anyhow::Result<()> {
+    match out {
+        Some(path) => std::fs::write(path, buf)
+            .map_err(|e| anyhow::anyhow!("write {} error:{}", path.display(), e)),
+        None => {
+            stdout.write_all(buf)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{run, Cli, KeyringArgs};
+    use clap::Parser;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use wd_passport::{Jwks, PassportEntity, PublicKeyFormat};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("wd_passport_cli_{}_{}", std::process::id(), name))
+    }
+
+    ///与测试中的命令行参数相同的证书：2023-02-06 至 2023-04-06 每 30 天一个窗口，1024 位
+    pub(crate) fn entity() -> PassportEntity {
+        entity_with_key("hello world")
+    }
+
+    pub(crate) fn entity_with_key(key: &str) -> PassportEntity {
+        //每次使用不同的 key 文件，并行的测试不会读到写了一半的文件
+        static SEQ: AtomicUsize = AtomicUsize::new(0);
+        let secret = temp_path(format!("entity_{}", SEQ.fetch_add(1, Ordering::SeqCst)).as_str());
+        std::fs::write(&secret, key).expect("写入 key 文件失败");
+        let keyring = KeyringArgs {
+            secret_file: Some(secret),
+            start: Some("2023-02-06".to_string()),
+            end: Some("2023-04-06".to_string()),
+            interval: Some("30d".to_string()),
+            bits: 1024,
+            snapshot: None,
+            password_file: None,
+        };
+        keyring.load().expect("证书生成失败")
+    }
+
+    fn passport(args: &[&str]) -> anyhow::Result<String> {
+        passport_stdin(args, b"")
+    }
+
+    fn passport_stdin(args: &[&str], stdin: &[u8]) -> anyhow::Result<String> {
+        let cli = Cli::try_parse_from([&["passport"], args].concat())?;
+        let mut out = vec![];
+        run(cli, &mut &stdin[..], &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn test_keyring_commands() {
+        let secret = temp_path("secret");
+        std::fs::write(&secret, "hello world\n").expect("写入 key 文件失败");
+        let secret = secret.to_str().unwrap();
+        let keyring = [
+            "--secret-file",
+            secret,
+            "--start",
+            "2023-02-06",
+            "--end",
+            "2023-04-06",
+            "--interval",
+            "30d",
+            "--bits",
+            "1024",
+        ];
+        let with = |cmd: &str, extra: &[&str]| -> anyhow::Result<String> {
+            passport(&[&[cmd], &keyring[..], extra].concat())
+        };
+
+        //key 文件末尾的换行被忽略
+        let entity = entity();
+        let expect = entity.to_public_fingerprint();
+        let list = with("list", &[]).expect("list 失败");
+        let lines: Vec<&str> = list.lines().collect();
+        assert_eq!(lines.len(), expect.len());
+        for (line, fp) in lines.iter().zip(expect.iter()) {
+            assert!(line.starts_with(fp.window.to_string().as_str()));
+            assert!(line.ends_with(fp.fingerprint.as_str()));
+        }
+
+        let jwks = Jwks::from_json(with("export", &["--format", "jwks"]).expect("jwks 导出失败"))
+            .expect("jwks 格式错误");
+        assert_eq!(jwks.keys.len(), expect.len());
+        let pem = with("export", &["--format", "spki"]).expect("pem 导出失败");
+        assert_eq!(
+            pem.matches("-----BEGIN PUBLIC KEY-----").count(),
+            expect.len()
+        );
+        let table = with("export", &["--format", "rust"]).expect("rust 导出失败");
+        assert!(table.contains(format!("INTERVAL_SEC: u64 = {};", 60 * 60 * 24 * 30).as_str()));
+        let der = entity
+            .export_public_keys(PublicKeyFormat::Pkcs1Der)
+            .expect("der 导出失败");
+        for (window, der) in der.iter() {
+            let bytes: Vec<String> = der.iter().map(|b| format!("0x{:02x},", b)).collect();
+            let block = table
+                .split(format!("        {},\n", window).as_str())
+                .nth(1)
+                .expect("缺少窗口");
+            let block: Vec<&str> = block
+                .split("        ],")
+                .next()
+                .expect("缺少公钥")
+                .split_whitespace()
+                .filter(|s| s.starts_with("0x"))
+                .collect();
+            assert_eq!(block, bytes);
+        }
+
+        //check 接受属于该证书的公钥，--timestamp 必须落在公钥对应的窗口
+        let window = expect[1].window;
+        let public = temp_path("public.pem");
+        std::fs::write(
+            &public,
+            entity
+                .to_public_pem()
+                .into_iter()
+                .find(|x| x.0 == window)
+                .unwrap()
+                .1,
+        )
+        .expect("写入公钥失败");
+        let public = public.to_str().unwrap();
+        let ok = with("check", &["--public-key", public]).expect("公钥校验失败");
+        assert_eq!(
+            ok.trim(),
+            format!("ok\t{}\t{}", window, expect[1].fingerprint)
+        );
+        let ts = (window + 10).to_string();
+        with(
+            "check",
+            &["--public-key", public, "--timestamp", ts.as_str()],
+        )
+        .expect("公钥校验失败");
+        let ts = expect[0].window.to_string();
+        assert!(with(
+            "check",
+            &["--public-key", public, "--timestamp", ts.as_str()]
+        )
+        .is_err());
+        let other = entity_with_key("another key");
+        std::fs::write(temp_path("other.pem"), other.to_public_pem()[0].1.as_str())
+            .expect("写入公钥失败");
+        let other = temp_path("other.pem");
+        assert!(with("check", &["--public-key", other.to_str().unwrap()]).is_err());
+
+        //快照不需要 key 即可列出相同的窗口
+        let snapshot = temp_path("snapshot.json");
+        with("snapshot", &["--out", snapshot.to_str().unwrap()]).expect("快照导出失败");
+        let from_snapshot =
+            passport(&["list", "--snapshot", snapshot.to_str().unwrap()]).expect("快照读取失败");
+        assert_eq!(from_snapshot, list);
+
+        assert!(passport(&["list", "--secret-file", secret]).is_err());
+        assert!(passport(&[
+            "list",
+            "--snapshot",
+            snapshot.to_str().unwrap(),
+            "--secret-file",
+            secret
+        ])
+        .is_err());
+        assert!(with("snapshot", &["--include-private"]).is_err());
+        for name in ["secret", "public.pem", "other.pem", "snapshot.json"] {
+            let _ = std::fs::remove_file(temp_path(name));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_agent_policy() {
+        use wd_passport::AgentOp;
+        let policy = super::agent_policy(&["1000:sign,verify".to_string(), "1001".to_string()])
+            .expect("白名单解析失败");
+        assert!(policy.is_allowed(1000, AgentOp::Sign));
+        assert!(!policy.is_allowed(1000, AgentOp::Decrypt));
+        assert!(policy.is_allowed(1001, AgentOp::Decrypt));
+        assert!(!policy.is_allowed(1002, AgentOp::Verify));
+        assert!(super::agent_policy(&["root".to_string()]).is_err());
+        assert!(super::agent_policy(&["1000:delete".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_data_commands() {
+        use crate::data::{exit_code, EXIT_BAD_SIGNATURE, EXIT_ERROR, EXIT_OUT_OF_RANGE};
+        let secret = temp_path("data_secret");
+        std::fs::write(&secret, "hello world").expect("写入 key 文件失败");
+        let secret = secret.to_str().unwrap();
+        let keyring = [
+            "--secret-file",
+            secret,
+            "--start",
+            "2023-02-06",
+            "--end",
+            "2023-04-06",
+            "--interval",
+            "30d",
+            "--bits",
+            "1024",
+        ];
+        let with = |cmd: &str, extra: &[&str], stdin: &[u8]| -> anyhow::Result<String> {
+            passport_stdin(&[&[cmd], &keyring[..], extra].concat(), stdin)
+        };
+        let ts = ["--timestamp", "1679919556"];
+
+        let sign = with("sign", &ts, b"hello world").expect("签名失败");
+        let sign = sign.trim().to_string();
+        let entity = entity();
+        let expect = entity
+            .sign_sha256("hello world", 1679919556)
+            .and_then(wd_passport::encode)
+            .expect("签名失败");
+        assert_eq!(sign, expect);
+
+        let data = temp_path("data.txt");
+        std::fs::write(&data, "hello world").expect("写入数据失败");
+        let data = data.to_str().unwrap();
+        let ok = with(
+            "verify",
+            &[&ts[..], &["--in", data, "--signature", sign.as_str()]].concat(),
+            b"",
+        )
+        .expect("验签失败");
+        assert_eq!(ok.trim(), "ok");
+        let sign_file = temp_path("data.sig");
+        std::fs::write(&sign_file, format!("{}\n", sign)).expect("写入签名失败");
+        with(
+            "verify",
+            &[&ts[..], &["--signature-file", sign_file.to_str().unwrap()]].concat(),
+            b"hello world",
+        )
+        .expect("验签失败");
+
+        //数据被篡改、签名格式错误、或用其他窗口验签都属于签名错误
+        let e = with(
+            "verify",
+            &[&ts[..], &["--signature", sign.as_str()]].concat(),
+            b"hello",
+        )
+        .unwrap_err();
+        assert_eq!(exit_code(&e), EXIT_BAD_SIGNATURE);
+        let e = with(
+            "verify",
+            &[&ts[..], &["--signature", "!!"]].concat(),
+            b"hello world",
+        )
+        .unwrap_err();
+        assert_eq!(exit_code(&e), EXIT_BAD_SIGNATURE);
+        let e = with(
+            "verify",
+            &["--timestamp", "2023-02-07", "--signature", sign.as_str()],
+            b"hello world",
+        )
+        .unwrap_err();
+        assert_eq!(exit_code(&e), EXIT_BAD_SIGNATURE);
+
+        //时间戳不在任何窗口内
+        let e = with(
+            "verify",
+            &["--timestamp", "2030-01-01", "--signature", sign.as_str()],
+            b"hello world",
+        )
+        .unwrap_err();
+        assert_eq!(exit_code(&e), EXIT_OUT_OF_RANGE);
+        let e = with("sign", &["--timestamp", "1000"], b"hello world").unwrap_err();
+        assert_eq!(exit_code(&e), EXIT_OUT_OF_RANGE);
+        //带绑定标记的数据不能普通签名
+        let e = with("sign", &ts, wd_passport::BOUND_SIGN_TAG).unwrap_err();
+        assert_eq!(exit_code(&e), EXIT_ERROR);
+
+        let cipher = with("encrypt", &ts, b"hello world").expect("加密失败");
+        let plain = with("decrypt", &ts, cipher.as_bytes()).expect("解密失败");
+        assert_eq!(plain, "hello world");
+        assert!(with("decrypt", &["--timestamp", "2023-02-07"], cipher.as_bytes()).is_err());
+        for name in ["data_secret", "data.txt", "data.sig"] {
+            let _ = std::fs::remove_file(temp_path(name));
+        }
+    }
+}
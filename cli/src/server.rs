@@ -0,0 +1,560 @@
+use crate::{export, ExportFormat};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use wd_passport::{decode, encode};
+use wd_passport::{window_start, PassportEntity, PassportError, Utc};
+use zeroize::Zeroizing;
+
+//请求体上限
+const MAX_BODY: u64 = 1024 * 1024;
+
+#[derive(Deserialize)]
+struct DataRequest {
+    data: String,
+    timestamp: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct VerifyRequest {
+    data: String,
+    signature: String,
+    timestamp: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct DecryptRequest {
+    ciphertext: String,
+    timestamp: Option<i64>,
+}
+
+#[derive(Serialize, Default)]
+struct WindowReply {
+    timestamp: i64,
+    window: i64,
+    key_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    valid: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ciphertext: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Reply {
+    fn json<T: Serialize>(status: u16, value: &T) -> Self {
+        match serde_json::to_vec(value) {
+            Ok(body) => Self {
+                status,
+                content_type: "application/json",
+                body,
+            },
+            Err(e) => Self::error(500, "internal", e.to_string().as_str()),
+        }
+    }
+    fn error(status: u16, error: &str, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::json!({ "error": error, "message": message })
+                .to_string()
+                .into_bytes(),
+        }
+    }
+}
+
+///HTTP 接口背后的证书与 API key，sign/decrypt 会用到私钥，需要 API key
+pub struct Service {
+    entity: PassportEntity,
+    key_ids: HashMap<i64, String>,
+    api_keys: Vec<Zeroizing<String>>,
+}
+
+impl Service {
+    ///api_keys 为空时 sign/decrypt 一律拒绝
+    pub fn new(entity: PassportEntity, api_keys: Vec<Zeroizing<String>>) -> Self {
+        let key_ids = entity
+            .to_public_fingerprint()
+            .into_iter()
+            .map(|fp| (fp.window, fp.key_id))
+            .collect();
+        Self {
+            entity,
+            key_ids,
+            api_keys,
+        }
+    }
+
+    pub fn handle(&self, method: &str, url: &str, api_key: Option<&str>, body: &[u8]) -> Reply {
+        let (path, query) = match url.split_once('?') {
+            Some((p, q)) => (p, q),
+            None => (url, ""),
+        };
+        match (method, path) {
+            ("GET", "/health") => Reply::json(
+                200,
+                &serde_json::json!({ "status": "ok", "windows": self.key_ids.len() }),
+            ),
+            ("GET", "/keys") => self.keys(query),
+            ("POST", "/sign") | ("POST", "/decrypt") if !self.authorized(api_key) => {
+                match self.api_keys.is_empty() {
+                    true => Reply::error(403, "forbidden", "no api key configured"),
+                    false => Reply::error(401, "unauthorized", "missing or invalid api key"),
+                }
+            }
+            ("POST", "/sign") => self.sign(body),
+            ("POST", "/verify") => self.verify(body),
+            ("POST", "/encrypt") => self.encrypt(body),
+            ("POST", "/decrypt") => self.decrypt(body),
+            (_, "/health" | "/keys" | "/sign" | "/verify" | "/encrypt" | "/decrypt") => {
+                Reply::error(405, "method_not_allowed", method)
+            }
+            _ => Reply::error(404, "not_found", path),
+        }
+    }
+
+    //逐字节比较全部 API key，耗时与匹配位置无关
+    fn authorized(&self, api_key: Option<&str>) -> bool {
+        let api_key = match api_key {
+            Some(s) => s.as_bytes(),
+            None => return false,
+        };
+        let mut found = false;
+        for key in self.api_keys.iter() {
+            let key = key.as_bytes();
+            let diff = key
+                .iter()
+                .zip(api_key.iter())
+                .fold(key.len() ^ api_key.len(), |acc, (a, b)| {
+                    acc | (a ^ b) as usize
+                });
+            found |= diff == 0;
+        }
+        found
+    }
+
+    fn keys(&self, query: &str) -> Reply {
+        let format = query
+            .split('&')
+            .find_map(|kv| kv.strip_prefix("format="))
+            .unwrap_or("jwks");
+        let format = match ExportFormat::from_str(format, true) {
+            Ok(o) => o,
+            Err(e) => return Reply::error(400, "bad_request", e.as_str()),
+        };
+        let content_type = match format {
+            ExportFormat::Jwks => "application/jwk-set+json",
+            ExportFormat::Pem | ExportFormat::Spki => "application/x-pem-file",
+            ExportFormat::Openssh | ExportFormat::Rust => "text/plain; charset=utf-8",
+        };
+        match export(&self.entity, format) {
+            Ok(body) => Reply {
+                status: 200,
+                content_type,
+                body,
+            },
+            Err(e) => Reply::error(500, "internal", e.to_string().as_str()),
+        }
+    }
+
+    //解析请求体并确认时间戳所在窗口存在
+    fn window<'a, T: Deserialize<'a>>(
+        &self,
+        body: &'a [u8],
+        timestamp: fn(&T) -> Option<i64>,
+    ) -> Result<(T, WindowReply), Reply> {
+        let req: T = match serde_json::from_slice(body) {
+            Ok(o) => o,
+            Err(e) => return Err(Reply::error(400, "bad_request", e.to_string().as_str())),
+        };
+        let t = timestamp(&req).unwrap_or_else(|| Utc::now().timestamp());
+        let interval = self.entity.upgrade_cycle().as_secs() as i64;
+        let window = window_start(t, interval);
+        let key_id = match self.key_ids.get(&window) {
+            Some(s) => s.clone(),
+            None => {
+                let message = PassportError::OutOfRange(t).to_string();
+                return Err(Reply::error(
+                    422,
+                    "timestamp_out_of_range",
+                    message.as_str(),
+                ));
+            }
+        };
+        let reply = WindowReply {
+            timestamp: t,
+            window,
+            key_id,
+            ..Default::default()
+        };
+        Ok((req, reply))
+    }
+
+    fn sign(&self, body: &[u8]) -> Reply {
+        let (req, mut reply) = match self.window::<DataRequest>(body, |r| r.timestamp) {
+            Ok(o) => o,
+            Err(e) => return e,
+        };
+        let data = match decode(req.data) {
+            Ok(o) => o,
+            Err(e) => return Reply::error(400, "bad_request", e.to_string().as_str()),
+        };
+        match self
+            .entity
+            .sign_sha256(data, reply.timestamp)
+            .and_then(encode)
+        {
+            Ok(sign) => {
+                reply.signature = Some(sign);
+                Reply::json(200, &reply)
+            }
+            Err(e) => failure(e, 500, "internal"),
+        }
+    }
+
+    fn verify(&self, body: &[u8]) -> Reply {
+        let (req, mut reply) = match self.window::<VerifyRequest>(body, |r| r.timestamp) {
+            Ok(o) => o,
+            Err(e) => return e,
+        };
+        let data = match decode(req.data) {
+            Ok(o) => o,
+            Err(e) => return Reply::error(400, "bad_request", e.to_string().as_str()),
+        };
+        //签名无法解码与签名不匹配同样视为无效签名
+        let sign = decode(req.signature).unwrap_or_default();
+        let valid = match self.entity.verify_sha256(data, sign, reply.timestamp) {
+            Ok(_) => true,
+            Err(e) => match e.downcast_ref::<PassportError>() {
+                Some(PassportError::BadSignature) => false,
+                _ => return failure(e, 500, "internal"),
+            },
+        };
+        reply.valid = Some(valid);
+        Reply::json(200, &reply)
+    }
+
+    fn encrypt(&self, body: &[u8]) -> Reply {
+        let (req, mut reply) = match self.window::<DataRequest>(body, |r| r.timestamp) {
+            Ok(o) => o,
+            Err(e) => return e,
+        };
+        let data = match decode(req.data) {
+            Ok(o) => o,
+            Err(e) => return Reply::error(400, "bad_request", e.to_string().as_str()),
+        };
+        match self.entity.encrypt(data, reply.timestamp).and_then(encode) {
+            Ok(cipher) => {
+                reply.ciphertext = Some(cipher);
+                Reply::json(200, &reply)
+            }
+            Err(e) => failure(e, 400, "encrypt_failed"),
+        }
+    }
+
+    fn decrypt(&self, body: &[u8]) -> Reply {
+        let (req, mut reply) = match self.window::<DecryptRequest>(body, |r| r.timestamp) {
+            Ok(o) => o,
+            Err(e) => return e,
+        };
+        let cipher = match decode(req.ciphertext) {
+            Ok(o) => o,
+            Err(e) => return Reply::error(400, "bad_request", e.to_string().as_str()),
+        };
+        match self
+            .entity
+            .decrypt(cipher, reply.timestamp)
+            .and_then(encode)
+        {
+            Ok(data) => {
+                reply.data = Some(data);
+                Reply::json(200, &reply)
+            }
+            Err(e) => failure(e, 400, "decrypt_failed"),
+        }
+    }
+}
+
+//按 PassportError 选择状态码，其他错误使用 status 与 code
+fn failure(e: anyhow::Error, status: u16, code: &str) -> Reply {
+    let message = e.to_string();
+    match e.downcast_ref::<PassportError>() {
+        Some(PassportError::OutOfRange(_)) => {
+            Reply::error(422, "timestamp_out_of_range", message.as_str())
+        }
+        Some(PassportError::BadSignature) => Reply::error(400, "bad_signature", message.as_str()),
+        Some(PassportError::Decrypt) => Reply::error(400, "decrypt_failed", message.as_str()),
+        //绑定签名的数据不能用普通签名伪造
+        Some(PassportError::BoundTag) => Reply::error(400, "bad_request", message.as_str()),
+        _ => Reply::error(status, code, message.as_str()),
+    }
+}
+
+///每行一个 API key，忽略空行和 # 开头的注释
+pub fn read_api_keys(path: &Path) -> anyhow::Result<Vec<Zeroizing<String>>> {
+    let text = Zeroizing::new(
+        std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("read {} error:{}", path.display(), e))?,
+    );
+    let keys: Vec<Zeroizing<String>> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| Zeroizing::new(l.to_string()))
+        .collect();
+    if keys.is_empty() {
+        anyhow::bail!("{} has no api key", path.display());
+    }
+    Ok(keys)
+}
+
+///基于 tiny_http 的同步 HTTP 服务
+pub struct HttpServer {
+    http: tiny_http::Server,
+    service: Service,
+    workers: AtomicUsize,
+}
+
+impl HttpServer {
+    pub fn bind(addr: &str, service: Service) -> anyhow::Result<Self> {
+        let http = tiny_http::Server::http(addr)
+            .map_err(|e| anyhow::anyhow!("listen on {} error:{}", addr, e))?;
+        Ok(Self {
+            http,
+            service,
+            workers: AtomicUsize::new(0),
+        })
+    }
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+    ///用 threads 个线程处理请求，直到调用 shutdown
+    pub fn run(self: &Arc<Self>, threads: usize) {
+        let threads = threads.max(1);
+        self.workers.store(threads, Ordering::SeqCst);
+        let workers: Vec<_> = (1..threads)
+            .map(|_| {
+                let server = self.clone();
+                std::thread::spawn(move || server.serve())
+            })
+            .collect();
+        self.serve();
+        for w in workers {
+            let _ = w.join();
+        }
+    }
+    //bin 中靠结束进程停止服务，这里供测试使用
+    #[allow(dead_code)]
+    pub fn shutdown(&self) {
+        for _ in 0..self.workers.load(Ordering::SeqCst).max(1) {
+            self.http.unblock();
+        }
+    }
+    fn serve(&self) {
+        while let Ok(mut request) = self.http.recv() {
+            let reply = self.reply(&mut request);
+            let content_type =
+                tiny_http::Header::from_bytes(&b"Content-Type"[..], reply.content_type.as_bytes())
+                    .expect("content type header");
+            let response = tiny_http::Response::from_data(reply.body)
+                .with_status_code(reply.status)
+                .with_header(content_type);
+            let _ = request.respond(response);
+        }
+    }
+    fn reply(&self, request: &mut tiny_http::Request) -> Reply {
+        let api_key = request.headers().iter().find_map(|h| {
+            let value = h.value.as_str();
+            if h.field.equiv("X-Api-Key") {
+                return Some(value.to_string());
+            }
+            match h.field.equiv("Authorization") {
+                true => value.strip_prefix("Bearer ").map(|s| s.to_string()),
+                false => None,
+            }
+        });
+        let mut body = vec![];
+        if let Err(e) = request
+            .as_reader()
+            .take(MAX_BODY + 1)
+            .read_to_end(&mut body)
+        {
+            return Reply::error(400, "bad_request", e.to_string().as_str());
+        }
+        if body.len() as u64 > MAX_BODY {
+            return Reply::error(
+                413,
+                "payload_too_large",
+                "request body is larger than 1 MiB",
+            );
+        }
+        let method = request.method().as_str().to_string();
+        self.service
+            .handle(method.as_str(), request.url(), api_key.as_deref(), &body)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HttpServer, Service};
+    use crate::test::entity;
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpStream};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use wd_passport::{decode, encode};
+    use wd_passport::{Jwks, PassportEntity, BOUND_SIGN_TAG};
+    use zeroize::Zeroizing;
+
+    fn request(
+        addr: SocketAddr,
+        method: &str,
+        path: &str,
+        api_key: Option<&str>,
+        body: &str,
+    ) -> (u16, serde_json::Value) {
+        let mut stream = TcpStream::connect(addr).expect("连接失败");
+        let mut head = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+            method,
+            path,
+            addr,
+            body.len()
+        );
+        if let Some(key) = api_key {
+            head += format!("Authorization: Bearer {}\r\n", key).as_str();
+        }
+        stream
+            .write_all(format!("{}\r\n{}", head, body).as_bytes())
+            .expect("发送请求失败");
+        let mut res = String::new();
+        stream.read_to_string(&mut res).expect("读取响应失败");
+        let (head, body) = res.split_once("\r\n\r\n").expect("响应格式错误");
+        let status = head
+            .split(' ')
+            .nth(1)
+            .and_then(|s| s.parse().ok())
+            .expect("响应状态码错误");
+        let body =
+            serde_json::from_str(body).unwrap_or(serde_json::Value::String(body.to_string()));
+        (status, body)
+    }
+
+    #[test]
+    fn test_http_service() {
+        let entity = entity();
+        let expect_sign = entity
+            .sign_sha256("hello world", 1679919556)
+            .and_then(encode)
+            .expect("签名失败");
+        let service = Service::new(entity, vec![Zeroizing::new("secret-key".to_string())]);
+        let server = Arc::new(HttpServer::bind("127.0.0.1:0", service).expect("监听失败"));
+        let addr = server.local_addr().expect("监听地址错误");
+        let worker = {
+            let server = server.clone();
+            std::thread::spawn(move || server.run(2))
+        };
+
+        let (status, body) = request(addr, "GET", "/health", None, "");
+        assert_eq!(status, 200);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["windows"], 3);
+
+        let (status, body) = request(addr, "GET", "/keys", None, "");
+        assert_eq!(status, 200);
+        let jwks: Jwks = serde_json::from_value(body).expect("jwks 格式错误");
+        assert_eq!(jwks.keys.len(), 3);
+        let (status, body) = request(addr, "GET", "/keys?format=pem", None, "");
+        assert_eq!(status, 200);
+        assert_eq!(
+            body.as_str()
+                .unwrap()
+                .matches("BEGIN RSA PUBLIC KEY")
+                .count(),
+            3
+        );
+        assert_eq!(request(addr, "GET", "/keys?format=xml", None, "").0, 400);
+
+        //签名需要 API key
+        let data = encode("hello world").unwrap();
+        let sign_req = serde_json::json!({ "data": data, "timestamp": 1679919556 }).to_string();
+        assert_eq!(request(addr, "POST", "/sign", None, &sign_req).0, 401);
+        assert_eq!(
+            request(addr, "POST", "/sign", Some("wrong"), &sign_req).0,
+            401
+        );
+        let (status, body) = request(addr, "POST", "/sign", Some("secret-key"), &sign_req);
+        assert_eq!(status, 200);
+        assert_eq!(body["signature"], expect_sign.as_str());
+        assert_eq!(body["window"], 1679616000);
+        assert_eq!(body["key_id"].as_str().map(|s| s.len()), Some(40));
+        let tagged = encode(BOUND_SIGN_TAG).unwrap();
+        let req = serde_json::json!({ "data": tagged, "timestamp": 1679919556 }).to_string();
+        let (status, body) = request(addr, "POST", "/sign", Some("secret-key"), &req);
+        assert_eq!(status, 400);
+        assert_eq!(body["error"], "bad_request");
+
+        let verify = |data: &str, ts: i64| {
+            let req =
+                serde_json::json!({ "data": data, "signature": expect_sign, "timestamp": ts });
+            request(addr, "POST", "/verify", None, req.to_string().as_str())
+        };
+        let (status, body) = verify(data.as_str(), 1679919556);
+        assert_eq!(status, 200);
+        assert_eq!(body["valid"], true);
+        let (status, body) = verify(encode("hello").unwrap().as_str(), 1679919556);
+        assert_eq!(status, 200);
+        assert_eq!(body["valid"], false);
+        let (status, body) = verify(data.as_str(), 1000);
+        assert_eq!(status, 422);
+        assert_eq!(body["error"], "timestamp_out_of_range");
+
+        let req = serde_json::json!({ "data": data, "timestamp": 1679919556 }).to_string();
+        let (status, body) = request(addr, "POST", "/encrypt", None, &req);
+        assert_eq!(status, 200);
+        let req = serde_json::json!({ "ciphertext": body["ciphertext"], "timestamp": 1679919556 })
+            .to_string();
+        assert_eq!(request(addr, "POST", "/decrypt", None, &req).0, 401);
+        let (status, body) = request(addr, "POST", "/decrypt", Some("secret-key"), &req);
+        assert_eq!(status, 200);
+        assert_eq!(
+            decode(body["data"].as_str().unwrap()).unwrap(),
+            b"hello world"
+        );
+
+        assert_eq!(
+            request(addr, "POST", "/sign", Some("secret-key"), "{").0,
+            400
+        );
+        assert_eq!(request(addr, "GET", "/sign", None, "").0, 405);
+        assert_eq!(request(addr, "GET", "/", None, "").0, 404);
+
+        server.shutdown();
+        worker.join().expect("服务线程异常退出");
+    }
+
+    #[test]
+    fn test_signing_disabled_without_api_key() {
+        let entity = PassportEntity::empty(Duration::from_secs(60)).expect("空证书创建失败");
+        let service = Service::new(entity, vec![]);
+        let reply = service.handle("POST", "/sign", Some(""), b"{}");
+        assert_eq!(reply.status, 403);
+        let reply = service.handle("POST", "/decrypt", None, b"{}");
+        assert_eq!(reply.status, 403);
+        assert_eq!(service.handle("GET", "/health", None, b"").status, 200);
+    }
+}
@@ -0,0 +1,334 @@
+use crate::Error;
+use crypto_bigint::modular::runtime_mod::{DynResidue, DynResidueParams};
+use crypto_bigint::{Encoding, Uint, U1024, U2048, U3072, U4096, U64};
+use sha2::{Digest, Sha256};
+
+///支持的最大密钥长度
+///
+///模幂运算在栈上完成，按模数长度选用 1024/2048/3072/4096 位的定长整数。
+///栈上最大的是 16 项的窗口表，4096 位密钥约 8 KiB，1024 位密钥约 2 KiB
+pub const MAX_KEY_BITS: usize = 4096;
+
+const MAX_KEY_BYTES: usize = MAX_KEY_BITS / 8;
+
+//DigestInfo 中 SHA-256 的 der 前缀，RFC 8017 9.2
+const SHA256_PREFIX: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+///借用 der 数据的 RSA 公钥，n 与 e 为去掉前导 0 的大端字节
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey<'a> {
+    n: &'a [u8],
+    e: u64,
+}
+
+//读取一个 der TLV，返回值与剩余数据
+fn read_tlv(input: &[u8], tag: u8) -> Result<(&[u8], &[u8]), Error> {
+    let (&t, rest) = input.split_first().ok_or(Error::InvalidKey)?;
+    if t != tag {
+        return Err(Error::InvalidKey);
+    }
+    let (&first, rest) = rest.split_first().ok_or(Error::InvalidKey)?;
+    let (len, rest) = match first {
+        0..=0x7f => (first as usize, rest),
+        0x81 => match rest.split_first() {
+            Some((&b, rest)) if b >= 0x80 => (b as usize, rest),
+            _ => return Err(Error::InvalidKey),
+        },
+        0x82 => match rest {
+            [a, b, rest @ ..] if *a != 0 => (((*a as usize) << 8) | *b as usize, rest),
+            _ => return Err(Error::InvalidKey),
+        },
+        _ => return Err(Error::InvalidKey),
+    };
+    if rest.len() < len {
+        return Err(Error::InvalidKey);
+    }
+    Ok(rest.split_at(len))
+}
+
+//正整数，去掉符号位的前导 0
+fn read_uint(input: &[u8]) -> Result<(&[u8], &[u8]), Error> {
+    let (value, rest) = read_tlv(input, 0x02)?;
+    let value = match value {
+        [] => return Err(Error::InvalidKey),
+        [b, ..] if *b >= 0x80 => return Err(Error::InvalidKey),
+        [0, next, ..] if *next >= 0x80 => &value[1..],
+        [0, _, ..] => return Err(Error::InvalidKey),
+        _ => value,
+    };
+    Ok((value, rest))
+}
+
+impl<'a> PublicKey<'a> {
+    ///解析 pkcs1 der：RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }
+    pub fn from_pkcs1_der(der: &'a [u8]) -> Result<Self, Error> {
+        let (seq, rest) = read_tlv(der, 0x30)?;
+        if !rest.is_empty() {
+            return Err(Error::InvalidKey);
+        }
+        let (n, seq) = read_uint(seq)?;
+        let (e, seq) = read_uint(seq)?;
+        if !seq.is_empty() {
+            return Err(Error::InvalidKey);
+        }
+        if n.len() > MAX_KEY_BYTES {
+            return Err(Error::KeyTooLarge);
+        }
+        if e.len() > 8 {
+            return Err(Error::InvalidKey);
+        }
+        let e = e.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+        //n 与 e 必须是奇数，e 至少为 3
+        if n.last().is_none_or(|b| b & 1 == 0) || e < 3 || e & 1 == 0 {
+            return Err(Error::InvalidKey);
+        }
+        Ok(Self { n, e })
+    }
+
+    ///模数的字节数，即签名长度
+    pub fn size(&self) -> usize {
+        self.n.len()
+    }
+
+    pub fn verify_sha256(&self, data: &[u8], sign: &[u8]) -> Result<(), Error> {
+        let digest: [u8; 32] = Sha256::digest(data).into();
+        self.verify_prehashed_sha256(&digest, sign)
+    }
+
+    ///digest 为数据的 SHA-256，适合流式计算摘要的场景
+    pub fn verify_prehashed_sha256(&self, digest: &[u8; 32], sign: &[u8]) -> Result<(), Error> {
+        let k = self.n.len();
+        //0x00 0x01 至少 8 个 0xff 0x00 DigestInfo
+        if sign.len() != k || k < SHA256_PREFIX.len() + digest.len() + 11 {
+            return Err(Error::BadSignature);
+        }
+        let mut buf = [0u8; MAX_KEY_BYTES];
+        let em = &mut buf[..k];
+        match k {
+            0..=128 => pow_e::<{ U1024::LIMBS }>(self.n, sign, self.e, em)?,
+            129..=256 => pow_e::<{ U2048::LIMBS }>(self.n, sign, self.e, em)?,
+            257..=384 => pow_e::<{ U3072::LIMBS }>(self.n, sign, self.e, em)?,
+            _ => pow_e::<{ U4096::LIMBS }>(self.n, sign, self.e, em)?,
+        }
+        let (padding, t) = em.split_at(k - SHA256_PREFIX.len() - digest.len());
+        let (prefix, hash) = t.split_at(SHA256_PREFIX.len());
+        let ok = padding[0] == 0
+            && padding[1] == 1
+            && padding[2..padding.len() - 1].iter().all(|b| *b == 0xff)
+            && padding[padding.len() - 1] == 0
+            && prefix == SHA256_PREFIX
+            && hash == digest;
+        if ok {
+            Ok(())
+        } else {
+            Err(Error::BadSignature)
+        }
+    }
+}
+
+//em = s^e mod n，写入与模数等长的 em；s >= n 时为无效签名
+fn pow_e<const LIMBS: usize>(n: &[u8], s: &[u8], e: u64, em: &mut [u8]) -> Result<(), Error>
+where
+    Uint<LIMBS>: Encoding,
+{
+    let n = uint::<LIMBS>(n);
+    let s = uint::<LIMBS>(s);
+    if s >= n {
+        return Err(Error::BadSignature);
+    }
+    //from_pkcs1_der 已经保证 n 是奇数
+    let params = DynResidueParams::new(&n);
+    let e_bits = 64 - e.leading_zeros() as usize;
+    let m = DynResidue::new(&s, params)
+        .pow_bounded_exp(&U64::from_u64(e), e_bits)
+        .retrieve()
+        .to_be_bytes();
+    let m = m.as_ref();
+    em.copy_from_slice(&m[m.len() - em.len()..]);
+    Ok(())
+}
+
+//大端字节左侧补 0 到 LIMBS 对应的定长
+fn uint<const LIMBS: usize>(bytes: &[u8]) -> Uint<LIMBS> {
+    let mut buf = [0u8; MAX_KEY_BYTES];
+    let len = Uint::<LIMBS>::BYTES;
+    buf[len - bytes.len()..len].copy_from_slice(bytes);
+    Uint::<LIMBS>::from_be_slice(&buf[..len])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::{decode, golden, pem_to_der};
+
+    #[test]
+    fn test_golden_signature() {
+        let golden = golden();
+        let list = golden["rsa_entity"].as_array().expect("缺少 rsa_entity");
+        assert!(!list.is_empty());
+        for item in list {
+            let der = pem_to_der(item["public_key_pem"].as_str().expect("缺少公钥"));
+            let key = PublicKey::from_pkcs1_der(der.as_slice()).expect("解析公钥失败");
+            assert_eq!(
+                key.size() * 8,
+                item["bit_size"].as_u64().expect("缺少位数") as usize
+            );
+            let message = item["message"].as_str().expect("缺少消息").as_bytes();
+            let sign = decode(item["signature"].as_str().expect("缺少签名"));
+            key.verify_sha256(message, sign.as_slice())
+                .expect("golden 签名验签失败");
+            assert_eq!(
+                key.verify_sha256(b"other message", sign.as_slice()),
+                Err(Error::BadSignature)
+            );
+            let mut tampered = sign.clone();
+            tampered[10] ^= 1;
+            assert_eq!(
+                key.verify_sha256(message, tampered.as_slice()),
+                Err(Error::BadSignature)
+            );
+            assert_eq!(
+                key.verify_sha256(message, &sign[1..]),
+                Err(Error::BadSignature)
+            );
+            //s >= n
+            assert_eq!(
+                key.verify_sha256(message, vec![0xff; sign.len()].as_slice()),
+                Err(Error::BadSignature)
+            );
+        }
+    }
+
+    //用 rsa crate 随机生成的密钥交叉验证，正常签名与各种畸形填充两边的结论必须一致
+    #[test]
+    fn test_cross_check_rsa() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+        use rsa::pkcs1::EncodeRsaPublicKey;
+        use rsa::{BigUint, Pkcs1v15Sign, PublicKey as _, PublicKeyParts, RsaPrivateKey};
+
+        let mut rng = StdRng::seed_from_u64(1675641600);
+        for bits in [1024, 2048, 3072, 4096] {
+            let prk = RsaPrivateKey::new(&mut rng, bits).expect("生成密钥失败");
+            let puk = prk.to_public_key();
+            let der = puk.to_pkcs1_der().expect("编码公钥失败");
+            let key = PublicKey::from_pkcs1_der(der.as_bytes()).expect("解析公钥失败");
+            let k = key.size();
+            assert_eq!(k * 8, bits);
+            //私钥运算 em^d mod n，用于构造任意填充的签名
+            let raw_sign = |em: &[u8]| {
+                let s = BigUint::from_bytes_be(em)
+                    .modpow(prk.d(), prk.n())
+                    .to_bytes_be();
+                let mut sign = vec![0u8; k - s.len()];
+                sign.extend(s);
+                sign
+            };
+            for _ in 0..4 {
+                let mut message = vec![0u8; rng.gen_range(0..256)];
+                rng.fill(message.as_mut_slice());
+                let digest: [u8; 32] = Sha256::digest(message.as_slice()).into();
+                //两个验签实现的结论必须相同
+                let agree = |sign: &[u8]| {
+                    let ours = key.verify_prehashed_sha256(&digest, sign).is_ok();
+                    let theirs = puk
+                        .verify(Pkcs1v15Sign::new::<rsa::sha2::Sha256>(), &digest, sign)
+                        .is_ok();
+                    assert_eq!(ours, theirs, "{} 位密钥的验签结论不一致", bits);
+                    ours
+                };
+
+                let sign = prk
+                    .sign(Pkcs1v15Sign::new::<rsa::sha2::Sha256>(), &digest)
+                    .expect("签名失败");
+                assert!(agree(sign.as_slice()));
+                key.verify_sha256(message.as_slice(), sign.as_slice())
+                    .expect("验签失败");
+
+                //0x00 0x01 0xff.. 0x00 DigestInfo
+                let mut em = vec![0xffu8; k];
+                em[0] = 0;
+                em[1] = 1;
+                let sep = k - SHA256_PREFIX.len() - digest.len() - 1;
+                em[sep] = 0;
+                em[sep + 1..sep + 1 + SHA256_PREFIX.len()].copy_from_slice(&SHA256_PREFIX);
+                em[k - digest.len()..].copy_from_slice(&digest);
+                assert_eq!(raw_sign(em.as_slice()), sign, "填充构造错误");
+
+                let mut cases = vec![];
+                let mut bad = em.clone();
+                bad[0] = 1;
+                cases.push(bad);
+                let mut bad = em.clone();
+                bad[1] = 2;
+                cases.push(bad);
+                let mut bad = em.clone();
+                bad[2] = 0xfe;
+                cases.push(bad);
+                let mut bad = em.clone();
+                bad[sep - 1] = 0;
+                cases.push(bad);
+                let mut bad = em.clone();
+                bad[sep] = 0xff;
+                cases.push(bad);
+                //DigestInfo 中的算法换成 SHA-384
+                let mut bad = em.clone();
+                bad[sep + 15] = 0x02;
+                cases.push(bad);
+                let mut bad = em.clone();
+                bad[k - 1] ^= 1;
+                cases.push(bad);
+                //只有 8 个 0xff，DigestInfo 之后是多余的数据
+                let mut bad = em.clone();
+                bad[10] = 0;
+                bad[11..11 + SHA256_PREFIX.len()].copy_from_slice(&SHA256_PREFIX);
+                bad[11 + SHA256_PREFIX.len()..11 + SHA256_PREFIX.len() + digest.len()]
+                    .copy_from_slice(&digest);
+                cases.push(bad);
+                let mut bad = vec![0u8; k];
+                rng.fill(&mut bad[1..]);
+                cases.push(bad);
+                for bad in cases {
+                    assert!(!agree(raw_sign(bad.as_slice()).as_slice()));
+                }
+                assert!(!agree(&sign[1..]));
+                assert!(!agree(vec![0xff; k].as_slice()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_der() {
+        let golden = golden();
+        let der = pem_to_der(
+            golden["rsa_entity"][0]["public_key_pem"]
+                .as_str()
+                .expect("缺少公钥"),
+        );
+        assert!(PublicKey::from_pkcs1_der(der.as_slice()).is_ok());
+        assert_eq!(
+            PublicKey::from_pkcs1_der(&der[..der.len() - 1]),
+            Err(Error::InvalidKey)
+        );
+        let mut trailing = der.clone();
+        trailing.push(0);
+        assert_eq!(
+            PublicKey::from_pkcs1_der(trailing.as_slice()),
+            Err(Error::InvalidKey)
+        );
+        assert_eq!(PublicKey::from_pkcs1_der(&[]), Err(Error::InvalidKey));
+        //SEQUENCE { INTEGER 4, INTEGER 3 }：偶数模数
+        assert_eq!(
+            PublicKey::from_pkcs1_der(&[0x30, 0x06, 0x02, 0x01, 0x04, 0x02, 0x01, 0x03]),
+            Err(Error::InvalidKey)
+        );
+        //SEQUENCE { INTEGER 5, INTEGER 1 }：e 太小
+        assert_eq!(
+            PublicKey::from_pkcs1_der(&[0x30, 0x06, 0x02, 0x01, 0x05, 0x02, 0x01, 0x01]),
+            Err(Error::InvalidKey)
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! 不依赖 std 与分配器的验签核心，供嵌入式设备使用
+//!
+//! 公钥表为各窗口的 pkcs1 der 公钥，`passport export --format rust` 会生成 static 的 WINDOW_KEYS 与 INTERVAL_SEC：
+//!
+//! ```ignore
+//! include!("keys.rs");
+//!
+//! let verifier = Verifier::new(&WINDOW_KEYS, INTERVAL_SEC)?;
+//! verifier.verify_sha256(data, sign, timestamp)?;
+//! ```
+//!
+//! 窗口划分与 PassportEntity 相同，签名为 PKCS#1 v1.5 SHA-256，密钥最大 4096 位
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod key;
+#[cfg(feature = "alloc")]
+mod owned;
+mod verifier;
+
+pub use key::{PublicKey, MAX_KEY_BITS};
+#[cfg(feature = "alloc")]
+pub use owned::OwnedVerifier;
+pub use verifier::{Verifier, WindowKey};
+
+use core::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    ///窗口间隔必须大于 0
+    InvalidInterval,
+    ///窗口起始时间没有按间隔对齐
+    UnalignedWindow(i64),
+    ///公钥表必须按窗口起始时间严格升序
+    UnsortedWindow(i64),
+    ///公钥不是合法的 pkcs1 der
+    InvalidKey,
+    ///公钥超过 MAX_KEY_BITS
+    KeyTooLarge,
+    ///时间戳不在任何窗口内
+    OutOfRange(i64),
+    ///签名与数据不匹配
+    BadSignature,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::InvalidInterval => write!(f, "upgrade cycle must be at least one second"),
+            Error::UnalignedWindow(w) => write!(f, "window[{}] is not aligned to interval", w),
+            Error::UnsortedWindow(w) => write!(f, "window[{}] is not in ascending order", w),
+            Error::InvalidKey => write!(f, "invalid pkcs1 der public key"),
+            Error::KeyTooLarge => write!(f, "public key is larger than {} bits", MAX_KEY_BITS),
+            Error::OutOfRange(ts) => write!(f, "timestamp[{}] Out of scope of verification", ts),
+            Error::BadSignature => write!(f, "signature verification failed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+///timestamp_sec 所在窗口的起始时间，interval 必须大于 0
+///
+///wd_passport 的 PassportEntity 与 PassportVerifier 也用它划分窗口
+pub fn window_start(timestamp_sec: i64, interval: i64) -> i64 {
+    timestamp_sec - timestamp_sec % interval
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+    use base64::Engine;
+
+    //pkcs1 pem 转 der
+    pub(crate) fn pem_to_der(pem: &str) -> Vec<u8> {
+        let body: String = pem.lines().filter(|l| !l.starts_with("-----")).collect();
+        STANDARD.decode(body).expect("pem 解码失败")
+    }
+
+    pub(crate) fn decode(s: &str) -> Vec<u8> {
+        URL_SAFE_NO_PAD.decode(s).expect("base64 解码失败")
+    }
+
+    pub(crate) fn golden() -> serde_json::Value {
+        serde_json::from_str(include_str!("../../testdata/golden_vectors.json"))
+            .expect("读取 golden vectors 失败")
+    }
+
+    pub(crate) fn fixture() -> serde_json::Value {
+        serde_json::from_str(include_str!("../../testdata/fixture.json")).expect("读取测试数据失败")
+    }
+}
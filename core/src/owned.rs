@@ -0,0 +1,103 @@
+use crate::{window_start, Error, PublicKey, Verifier, WindowKey};
+use alloc::vec::Vec;
+
+///持有公钥的验签器，用于运行时从网络或存储加载公钥的场景
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedVerifier {
+    interval: i64,
+    keys: Vec<(i64, Vec<u8>)>,
+}
+
+impl OwnedVerifier {
+    pub fn new(interval_sec: u64) -> Result<Self, Error> {
+        //借用 Verifier 的检查
+        Verifier::new(&[], interval_sec)?;
+        Ok(Self {
+            interval: interval_sec as i64,
+            keys: Vec::new(),
+        })
+    }
+
+    pub fn interval_sec(&self) -> u64 {
+        self.interval as u64
+    }
+
+    ///加入或替换一个窗口的 pkcs1 der 公钥
+    pub fn insert(&mut self, window: i64, der: Vec<u8>) -> Result<(), Error> {
+        if window % self.interval != 0 {
+            return Err(Error::UnalignedWindow(window));
+        }
+        PublicKey::from_pkcs1_der(der.as_slice())?;
+        match self.keys.binary_search_by_key(&window, |k| k.0) {
+            Ok(i) => self.keys[i].1 = der,
+            Err(i) => self.keys.insert(i, (window, der)),
+        }
+        Ok(())
+    }
+
+    ///本公钥表的窗口列表，可用于构建借用它的 Verifier
+    pub fn keys(&self) -> Vec<WindowKey<'_>> {
+        self.keys
+            .iter()
+            .map(|(w, der)| WindowKey::new(*w, der.as_slice()))
+            .collect()
+    }
+
+    pub fn window_of(&self, timestamp_sec: i64) -> Option<i64> {
+        let window = window_start(timestamp_sec, self.interval);
+        self.keys
+            .binary_search_by_key(&window, |k| k.0)
+            .ok()
+            .map(|_| window)
+    }
+
+    pub fn public_key(&self, timestamp_sec: i64) -> Option<PublicKey<'_>> {
+        let window = self.window_of(timestamp_sec)?;
+        let i = self.keys.binary_search_by_key(&window, |k| k.0).ok()?;
+        PublicKey::from_pkcs1_der(self.keys[i].1.as_slice()).ok()
+    }
+
+    pub fn verify_sha256(&self, data: &[u8], sign: &[u8], timestamp_sec: i64) -> Result<(), Error> {
+        self.public_key(timestamp_sec)
+            .ok_or(Error::OutOfRange(timestamp_sec))?
+            .verify_sha256(data, sign)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::{decode, fixture, pem_to_der};
+
+    #[test]
+    fn test_owned_verifier() {
+        let interval = 60 * 60 * 24 * 30;
+        let timestamp = 1679919556;
+        let pems: Vec<(i64, String)> =
+            serde_json::from_value(fixture()["public_pem"].clone()).expect("读取 pem 失败");
+        let mut verifier = OwnedVerifier::new(interval).expect("构建验签器失败");
+        //乱序插入
+        for (w, pem) in pems.iter().rev() {
+            verifier
+                .insert(*w, pem_to_der(pem.as_str()))
+                .expect("加入公钥失败");
+        }
+        let sign = decode(fixture()["signature"].as_str().expect("缺少签名"));
+        verifier
+            .verify_sha256(b"hello world", sign.as_slice(), timestamp)
+            .expect("验签失败");
+        assert_eq!(
+            verifier.verify_sha256(b"hello world", sign.as_slice(), 1),
+            Err(Error::OutOfRange(1))
+        );
+        let keys = verifier.keys();
+        let borrowed = Verifier::new(keys.as_slice(), interval).expect("构建借用验签器失败");
+        assert_eq!(borrowed.window_of(timestamp), verifier.window_of(timestamp));
+        assert_eq!(
+            verifier.insert(pems[0].0 + 1, pem_to_der(pems[0].1.as_str())),
+            Err(Error::UnalignedWindow(pems[0].0 + 1))
+        );
+        assert_eq!(verifier.insert(pems[0].0, vec![0]), Err(Error::InvalidKey));
+        assert_eq!(OwnedVerifier::new(0), Err(Error::InvalidInterval));
+    }
+}
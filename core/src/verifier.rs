@@ -0,0 +1,170 @@
+use crate::{window_start, Error, PublicKey};
+
+///一个窗口的公钥，der 为 pkcs1 der
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowKey<'a> {
+    pub window: i64,
+    pub der: &'a [u8],
+}
+
+impl<'a> WindowKey<'a> {
+    pub const fn new(window: i64, der: &'a [u8]) -> Self {
+        Self { window, der }
+    }
+}
+
+///借用公钥表的验签器，不做任何分配
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Verifier<'a> {
+    interval: i64,
+    keys: &'a [WindowKey<'a>],
+}
+
+impl<'a> Verifier<'a> {
+    ///keys 必须按窗口起始时间严格升序，且每个公钥都能解析
+    pub fn new(keys: &'a [WindowKey<'a>], interval_sec: u64) -> Result<Self, Error> {
+        let interval = i64::try_from(interval_sec).map_err(|_| Error::InvalidInterval)?;
+        if interval <= 0 {
+            return Err(Error::InvalidInterval);
+        }
+        let mut last = None;
+        for key in keys {
+            if key.window % interval != 0 {
+                return Err(Error::UnalignedWindow(key.window));
+            }
+            if last.is_some_and(|w| w >= key.window) {
+                return Err(Error::UnsortedWindow(key.window));
+            }
+            PublicKey::from_pkcs1_der(key.der)?;
+            last = Some(key.window);
+        }
+        Ok(Self { interval, keys })
+    }
+
+    pub fn interval_sec(&self) -> u64 {
+        self.interval as u64
+    }
+
+    pub fn keys(&self) -> &'a [WindowKey<'a>] {
+        self.keys
+    }
+
+    ///timestamp_sec 所在窗口的起始时间，没有该窗口的公钥时返回 None
+    pub fn window_of(&self, timestamp_sec: i64) -> Option<i64> {
+        self.find(timestamp_sec).map(|k| k.window)
+    }
+
+    pub fn public_key(&self, timestamp_sec: i64) -> Option<PublicKey<'a>> {
+        //构建时已经检查过所有公钥
+        self.find(timestamp_sec)
+            .and_then(|k| PublicKey::from_pkcs1_der(k.der).ok())
+    }
+
+    pub fn verify_sha256(&self, data: &[u8], sign: &[u8], timestamp_sec: i64) -> Result<(), Error> {
+        self.public_key(timestamp_sec)
+            .ok_or(Error::OutOfRange(timestamp_sec))?
+            .verify_sha256(data, sign)
+    }
+
+    pub fn verify_prehashed_sha256(
+        &self,
+        digest: &[u8; 32],
+        sign: &[u8],
+        timestamp_sec: i64,
+    ) -> Result<(), Error> {
+        self.public_key(timestamp_sec)
+            .ok_or(Error::OutOfRange(timestamp_sec))?
+            .verify_prehashed_sha256(digest, sign)
+    }
+
+    fn find(&self, timestamp_sec: i64) -> Option<&'a WindowKey<'a>> {
+        let window = window_start(timestamp_sec, self.interval);
+        self.keys
+            .binary_search_by_key(&window, |k| k.window)
+            .ok()
+            .map(|i| &self.keys[i])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::{decode, fixture, pem_to_der};
+
+    const INTERVAL: u64 = 60 * 60 * 24 * 30;
+    const TIMESTAMP: i64 = 1679919556;
+
+    fn ders() -> Vec<(i64, Vec<u8>)> {
+        let pems: Vec<(i64, String)> =
+            serde_json::from_value(fixture()["public_pem"].clone()).expect("读取 pem 失败");
+        pems.into_iter()
+            .map(|(w, pem)| (w, pem_to_der(pem.as_str())))
+            .collect()
+    }
+
+    #[test]
+    fn test_verifier() {
+        let ders = ders();
+        let keys: Vec<WindowKey> = ders
+            .iter()
+            .map(|(w, der)| WindowKey::new(*w, der.as_slice()))
+            .collect();
+        let verifier = Verifier::new(keys.as_slice(), INTERVAL).expect("构建验签器失败");
+        let sign = decode(fixture()["signature"].as_str().expect("缺少签名"));
+        verifier
+            .verify_sha256(b"hello world", sign.as_slice(), TIMESTAMP)
+            .expect("验签失败");
+        assert_eq!(
+            verifier.verify_sha256(b"hello", sign.as_slice(), TIMESTAMP),
+            Err(Error::BadSignature)
+        );
+        assert_eq!(
+            verifier.verify_sha256(b"hello world", sign.as_slice(), 1),
+            Err(Error::OutOfRange(1))
+        );
+        //其他窗口的公钥不能验证该签名
+        assert_eq!(
+            verifier.verify_sha256(b"hello world", sign.as_slice(), keys[0].window),
+            Err(Error::BadSignature)
+        );
+        assert_eq!(verifier.window_of(TIMESTAMP), Some(keys[2].window));
+        assert_eq!(verifier.window_of(1), None);
+    }
+
+    #[test]
+    fn test_invalid_table() {
+        let ders = ders();
+        let keys: Vec<WindowKey> = ders
+            .iter()
+            .map(|(w, der)| WindowKey::new(*w, der.as_slice()))
+            .collect();
+        assert_eq!(
+            Verifier::new(keys.as_slice(), 0),
+            Err(Error::InvalidInterval)
+        );
+        let reversed: Vec<WindowKey> = keys.iter().rev().copied().collect();
+        assert_eq!(
+            Verifier::new(reversed.as_slice(), INTERVAL),
+            Err(Error::UnsortedWindow(keys[1].window))
+        );
+        let misaligned = [WindowKey::new(keys[0].window + 1, keys[0].der)];
+        assert_eq!(
+            Verifier::new(&misaligned, INTERVAL),
+            Err(Error::UnalignedWindow(keys[0].window + 1))
+        );
+        let invalid = [WindowKey::new(keys[0].window, &keys[0].der[1..])];
+        assert_eq!(Verifier::new(&invalid, INTERVAL), Err(Error::InvalidKey));
+    }
+
+    //公钥表可以是 static，不需要分配器
+    #[test]
+    fn test_static_table() {
+        static EMPTY: [WindowKey; 0] = [];
+        let verifier = Verifier::new(&EMPTY, INTERVAL).expect("构建验签器失败");
+        assert_eq!(verifier.window_of(TIMESTAMP), None);
+        assert_eq!(
+            verifier.verify_sha256(b"", b"", TIMESTAMP),
+            Err(Error::OutOfRange(TIMESTAMP))
+        );
+    }
+}
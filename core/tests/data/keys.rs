@@ -0,0 +1,49 @@
+// passport export --format rust
+pub const INTERVAL_SEC: u64 = 2592000;
+pub static WINDOW_KEYS: [wd_passport_core::WindowKey; 3] = [
+    wd_passport_core::WindowKey::new(
+        1674432000,
+        &[
+            0x30, 0x81, 0x89, 0x02, 0x81, 0x81, 0x00, 0xbe, 0xf5, 0xbe, 0x6b, 0x7c, 0x24, 0x01,
+            0x90, 0xe2, 0xb0, 0x2a, 0x3d, 0x39, 0xde, 0x89, 0xea, 0xda, 0xf6, 0x07, 0xfb, 0x6e,
+            0xfe, 0x89, 0x48, 0x8a, 0x22, 0xb4, 0x8d, 0xcb, 0x71, 0x70, 0x1d, 0x9e, 0xb1, 0xc9,
+            0x4b, 0x1f, 0x94, 0xa2, 0xcd, 0xff, 0x07, 0x54, 0x25, 0x98, 0x21, 0x67, 0x8a, 0xb4,
+            0x32, 0xb5, 0xfe, 0x03, 0x85, 0x08, 0xab, 0x64, 0x33, 0x3d, 0xa0, 0x8f, 0x10, 0x1f,
+            0x0c, 0x51, 0xf1, 0x3d, 0xd9, 0xd3, 0x62, 0x5b, 0x82, 0x34, 0xa0, 0x9e, 0x08, 0xf4,
+            0xa7, 0xad, 0x09, 0xaa, 0x0d, 0x4a, 0x68, 0x83, 0xf2, 0x8a, 0xdc, 0xea, 0xa1, 0x15,
+            0xa1, 0xcf, 0xcd, 0x5e, 0xa8, 0xb0, 0x09, 0x12, 0x37, 0x1a, 0x04, 0x7f, 0x8b, 0x75,
+            0xf3, 0xd7, 0xe0, 0x01, 0xdf, 0xcf, 0xc1, 0x6a, 0xfa, 0x1f, 0x48, 0x6b, 0x6a, 0x77,
+            0x64, 0x89, 0xfb, 0xb4, 0xae, 0x67, 0xf5, 0x24, 0xe9, 0x02, 0x03, 0x01, 0x00, 0x01,
+        ],
+    ),
+    wd_passport_core::WindowKey::new(
+        1677024000,
+        &[
+            0x30, 0x81, 0x89, 0x02, 0x81, 0x81, 0x00, 0xb7, 0x43, 0x6e, 0x6e, 0xe3, 0x57, 0xa6,
+            0x3e, 0x11, 0x61, 0xfb, 0x0a, 0x96, 0xaa, 0x5c, 0x8a, 0x62, 0x8c, 0x4e, 0xd2, 0xdf,
+            0x26, 0x30, 0xf2, 0xf6, 0xfa, 0xe2, 0xcf, 0x7d, 0x6a, 0xbf, 0xd1, 0x54, 0x19, 0x18,
+            0xff, 0xa9, 0x1a, 0x70, 0xd7, 0x47, 0x08, 0xf3, 0x77, 0x0a, 0x9e, 0x1f, 0x60, 0xe9,
+            0x73, 0x84, 0x98, 0x11, 0x87, 0xd5, 0x74, 0xef, 0xa8, 0x92, 0xdb, 0xae, 0xeb, 0xb5,
+            0xfc, 0xcf, 0xd2, 0x3c, 0x2e, 0xf3, 0x92, 0xbe, 0xb6, 0xec, 0x39, 0xbd, 0x81, 0x20,
+            0x69, 0x80, 0x41, 0x62, 0x23, 0x0f, 0xff, 0x7c, 0x2e, 0xff, 0x9d, 0x87, 0x89, 0xf9,
+            0xf0, 0x2e, 0xcb, 0x01, 0x0b, 0xd9, 0xdb, 0x5e, 0x3c, 0xfa, 0x40, 0x4d, 0x7b, 0x68,
+            0x8b, 0xc3, 0x0c, 0x1b, 0x81, 0x35, 0x29, 0x71, 0x5c, 0x44, 0x96, 0x93, 0x4d, 0x3c,
+            0x38, 0x92, 0x5d, 0x8a, 0xf7, 0x54, 0xe8, 0x86, 0x7b, 0x02, 0x03, 0x01, 0x00, 0x01,
+        ],
+    ),
+    wd_passport_core::WindowKey::new(
+        1679616000,
+        &[
+            0x30, 0x81, 0x89, 0x02, 0x81, 0x81, 0x00, 0xd1, 0x63, 0xe8, 0x70, 0xdf, 0x56, 0x5e,
+            0xe5, 0xf0, 0x46, 0x51, 0xbe, 0xcc, 0x9c, 0xea, 0x53, 0x5f, 0xcb, 0xc4, 0xd8, 0x9e,
+            0x84, 0x98, 0xbb, 0x1a, 0xb4, 0x42, 0xdd, 0xf8, 0x63, 0x67, 0x9a, 0xb5, 0x30, 0xcc,
+            0xb5, 0x2c, 0x4b, 0x79, 0x43, 0xbb, 0xa5, 0x49, 0x05, 0x56, 0x12, 0x26, 0xd6, 0x00,
+            0x80, 0x7d, 0x76, 0x9d, 0xc4, 0xaf, 0x16, 0xf8, 0xcb, 0x2d, 0xae, 0xd8, 0xc5, 0xf9,
+            0x5d, 0xa8, 0x1e, 0x8b, 0x17, 0x96, 0x77, 0x54, 0xc5, 0x5f, 0xcd, 0x6a, 0xab, 0x46,
+            0x6a, 0x6f, 0xdc, 0x13, 0xa9, 0x15, 0xff, 0x6c, 0xc0, 0xa7, 0x1f, 0x8e, 0x5a, 0x3e,
+            0xe9, 0x77, 0x39, 0x88, 0x49, 0x79, 0xea, 0xbb, 0x70, 0xf6, 0x88, 0x65, 0xb5, 0x31,
+            0x62, 0xc3, 0x27, 0x86, 0x3d, 0x29, 0x0d, 0x8e, 0xa5, 0xa5, 0xe7, 0xad, 0x6b, 0x07,
+            0x05, 0xd9, 0x67, 0xb5, 0x6a, 0xf6, 0x04, 0xdf, 0x6b, 0x02, 0x03, 0x01, 0x00, 0x01,
+        ],
+    ),
+];
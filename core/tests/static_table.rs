@@ -0,0 +1,27 @@
+//! data/keys.rs 由 passport export --format rust 生成：key "hello world"，1024 位，2023-02-06 至 2023-04-06
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use wd_passport_core::{Error, Verifier};
+
+include!("data/keys.rs");
+
+#[test]
+fn test_static_table() {
+    let fixture: serde_json::Value =
+        serde_json::from_str(include_str!("../../testdata/fixture.json"))
+            .expect("读取测试数据失败");
+    let timestamp = fixture["timestamp"].as_i64().expect("缺少时间戳");
+    let sign = URL_SAFE_NO_PAD
+        .decode(fixture["signature"].as_str().expect("缺少签名"))
+        .expect("签名解码失败");
+    let verifier = Verifier::new(&WINDOW_KEYS, INTERVAL_SEC).expect("构建验签器失败");
+    verifier
+        .verify_sha256(b"hello world", sign.as_slice(), timestamp)
+        .expect("验签失败");
+    assert_eq!(
+        verifier.verify_sha256(b"hello", sign.as_slice(), timestamp),
+        Err(Error::BadSignature)
+    );
+    let windows: Vec<i64> = verifier.keys().iter().map(|k| k.window).collect();
+    assert_eq!(windows, vec![1674432000, 1677024000, 1679616000]);
+}
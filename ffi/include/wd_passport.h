@@ -0,0 +1,164 @@
+#ifndef WD_PASSPORT_H
+#define WD_PASSPORT_H
+
+/* Generated by cbindgen from wd_passport/src/clib, do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define PASSPORT_OK 0
+
+//其他错误
+#define PASSPORT_ERR -1
+
+//空指针等非法参数
+#define PASSPORT_ERR_INVALID_ARGUMENT -2
+
+//输出缓冲区不足，*out_len 为需要的字节数
+#define PASSPORT_ERR_BUFFER_TOO_SMALL -3
+
+//时间戳不在任何窗口内
+#define PASSPORT_ERR_OUT_OF_RANGE -4
+
+//签名与数据不匹配
+#define PASSPORT_ERR_BAD_SIGNATURE -5
+
+//密文无法用该窗口的私钥解密
+#define PASSPORT_ERR_DECRYPT -6
+
+//无法用 key 与时间表生成证书
+#define PASSPORT_ERR_INIT -7
+
+//不透明句柄
+typedef struct PassportHandle PassportHandle;
+
+//日志回调，每次调用失败时以错误码和 utf8 错误描述调用，message 只在回调期间有效
+typedef void (*PassportLogCallback)(int code, const char *message);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+//签名写入 sign，sign 中第一个 0 字节之前的长度即缓冲区大小，返回签名长度
+int sign(const char *data,
+         long long timestamp,
+         char *sign);
+
+//签名按 C 字符串读取，包含 0 字节的签名请使用 passport_verify_v2
+int verify(const char *data, const char *sign, long long timestamp);
+
+//密文写入 ciphertext，缓冲区不足时截断，返回写入的长度
+int encrypt(const char *data, long long timestamp, char *ciphertext);
+
+//解密 plaintext 中的密文，明文写入 data，缓冲区不足时截断，返回写入的长度
+int decrypt(const char *plaintext,
+            const char *data,
+            long long timestamp);
+
+//当前线程最近一次失败的错误描述，没有错误时返回 NULL
+//返回的指针在当前线程下一次调用本库函数之前有效，调用方不能释放
+const char *passport_last_error(void);
+
+//当前线程最近一次失败的错误码，没有错误时返回 PASSPORT_OK
+int passport_last_error_code(void);
+
+//注册日志回调，传入 NULL 取消；回调可能在任意调用线程中执行
+void passport_set_log_callback(PassportLogCallback callback);
+
+//用 key 与时间表(unix 秒)重新生成全局证书，供 sign/verify 与 *_v2 接口使用
+int passport_init(const uint8_t *key,
+                  size_t key_len,
+                  int64_t start_sec,
+                  int64_t end_sec,
+                  uint64_t interval_sec,
+                  size_t bits);
+
+//创建证书句柄，失败时返回 NULL，原因由 passport_last_error 取得
+struct PassportHandle *passport_new(const uint8_t *key,
+                                    size_t key_len,
+                                    int64_t start_sec,
+                                    int64_t end_sec,
+                                    uint64_t interval_sec,
+                                    size_t bits);
+
+//释放句柄，传入 NULL 时什么也不做
+void passport_free(struct PassportHandle *handle);
+
+int passport_sign(const struct PassportHandle *handle,
+                  const uint8_t *data,
+                  size_t data_len,
+                  int64_t timestamp,
+                  uint8_t *out,
+                  size_t out_cap,
+                  size_t *out_len);
+
+int passport_verify(const struct PassportHandle *handle,
+                    const uint8_t *data,
+                    size_t data_len,
+                    const uint8_t *sign,
+                    size_t sign_len,
+                    int64_t timestamp);
+
+int passport_encrypt(const struct PassportHandle *handle,
+                     const uint8_t *data,
+                     size_t data_len,
+                     int64_t timestamp,
+                     uint8_t *out,
+                     size_t out_cap,
+                     size_t *out_len);
+
+int passport_decrypt(const struct PassportHandle *handle,
+                     const uint8_t *data,
+                     size_t data_len,
+                     int64_t timestamp,
+                     uint8_t *out,
+                     size_t out_cap,
+                     size_t *out_len);
+
+//按窗口顺序拼接的全部 pkcs1 公钥 PEM，不包含结尾的 0 字节
+int passport_export_public_pem(const struct PassportHandle *handle,
+                               uint8_t *out,
+                               size_t out_cap,
+                               size_t *out_len);
+
+//timestamp 所在窗口的 pkcs1 公钥 PEM，不包含结尾的 0 字节
+int passport_window_public_pem(const struct PassportHandle *handle,
+                               int64_t timestamp,
+                               uint8_t *out,
+                               size_t out_cap,
+                               size_t *out_len);
+
+//用全局证书签名，签名长度等于 rsa 位数 / 8
+int passport_sign_v2(const uint8_t *data,
+                     size_t data_len,
+                     int64_t timestamp,
+                     uint8_t *out,
+                     size_t out_cap,
+                     size_t *out_len);
+
+//用全局证书验签，成功返回 PASSPORT_OK
+int passport_verify_v2(const uint8_t *data,
+                       size_t data_len,
+                       const uint8_t *sign,
+                       size_t sign_len,
+                       int64_t timestamp);
+
+int passport_encrypt_v2(const uint8_t *data,
+                        size_t data_len,
+                        int64_t timestamp,
+                        uint8_t *out,
+                        size_t out_cap,
+                        size_t *out_len);
+
+int passport_decrypt_v2(const uint8_t *data,
+                        size_t data_len,
+                        int64_t timestamp,
+                        uint8_t *out,
+                        size_t out_cap,
+                        size_t *out_len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* WD_PASSPORT_H */
@@ -0,0 +1,10 @@
+//! 把 wd_passport 的 C 接口打包为 libwd_passport.so 与 libwd_passport.a，头文件见 include/wd_passport.h
+//!
+//! 导出的符号全部定义在 wd_passport::clib 中，这里只负责把它们链接进产物
+pub use ::wd_passport::{
+    decrypt, encrypt, passport_decrypt, passport_decrypt_v2, passport_encrypt, passport_encrypt_v2,
+    passport_export_public_pem, passport_free, passport_init, passport_last_error,
+    passport_last_error_code, passport_new, passport_set_log_callback, passport_sign,
+    passport_sign_v2, passport_verify, passport_verify_v2, passport_window_public_pem, sign,
+    verify, PassportHandle, PassportLogCallback,
+};
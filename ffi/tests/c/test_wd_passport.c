@@ -0,0 +1,154 @@
+/* 调用 wd_passport.h 中的每一个函数，失败时返回非 0 */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "wd_passport.h"
+
+#define START 1675641600
+#define END 1680739200
+#define INTERVAL (60 * 60 * 24 * 30)
+#define TIMESTAMP 1679919556
+
+static int failures = 0;
+
+#define CHECK(cond)                                                                      \
+    do {                                                                                 \
+        if (!(cond)) {                                                                   \
+            const char *err = passport_last_error();                                     \
+            fprintf(stderr, "%s:%d: check failed: %s (last error %d: %s)\n", __FILE__,   \
+                    __LINE__, #cond, passport_last_error_code(), err ? err : "none");    \
+            failures++;                                                                  \
+        }                                                                                \
+    } while (0)
+
+static int logged_code = PASSPORT_OK;
+
+static void on_log(int code, const char *message) {
+    if (message != NULL) {
+        logged_code = code;
+    }
+}
+
+static void test_errors(void) {
+    passport_set_log_callback(on_log);
+    size_t len = 0;
+    CHECK(passport_sign_v2(NULL, 3, TIMESTAMP, NULL, 0, &len) == PASSPORT_ERR_INVALID_ARGUMENT);
+    CHECK(passport_last_error_code() == PASSPORT_ERR_INVALID_ARGUMENT);
+    CHECK(passport_last_error() != NULL);
+    CHECK(logged_code == PASSPORT_ERR_INVALID_ARGUMENT);
+    passport_set_log_callback(NULL);
+    logged_code = PASSPORT_OK;
+    CHECK(passport_sign_v2((const uint8_t *)"x", 1, 1, NULL, 0, &len) == PASSPORT_ERR_OUT_OF_RANGE);
+    CHECK(logged_code == PASSPORT_OK);
+    CHECK(passport_new((const uint8_t *)"k", 1, START, END, 0, 1024) == NULL);
+    CHECK(passport_last_error_code() == PASSPORT_ERR_INIT);
+}
+
+static void test_handle(void) {
+    const uint8_t key[] = "hello world";
+    PassportHandle *handle = passport_new(key, sizeof(key) - 1, START, END, INTERVAL, 1024);
+    CHECK(handle != NULL);
+    CHECK(passport_last_error() == NULL);
+    if (handle == NULL) {
+        return;
+    }
+    const uint8_t data[] = "hello\0world";
+    size_t data_len = sizeof(data) - 1;
+    uint8_t sig[128];
+    size_t sig_len = 0;
+    CHECK(passport_sign(handle, data, data_len, TIMESTAMP, sig, sizeof(sig), &sig_len) == PASSPORT_OK);
+    CHECK(sig_len == 128);
+    CHECK(passport_verify(handle, data, data_len, sig, sig_len, TIMESTAMP) == PASSPORT_OK);
+    CHECK(passport_verify(handle, data, 5, sig, sig_len, TIMESTAMP) == PASSPORT_ERR_BAD_SIGNATURE);
+
+    uint8_t cipher[128];
+    size_t cipher_len = 0;
+    CHECK(passport_encrypt(handle, data, data_len, TIMESTAMP, cipher, sizeof(cipher), &cipher_len) == PASSPORT_OK);
+    uint8_t plain[64];
+    size_t plain_len = 0;
+    CHECK(passport_decrypt(handle, cipher, cipher_len, TIMESTAMP, plain, 4, &plain_len) == PASSPORT_ERR_BUFFER_TOO_SMALL);
+    CHECK(plain_len == data_len);
+    CHECK(passport_decrypt(handle, cipher, cipher_len, TIMESTAMP, plain, sizeof(plain), &plain_len) == PASSPORT_OK);
+    CHECK(plain_len == data_len && memcmp(plain, data, data_len) == 0);
+
+    size_t pem_len = 0;
+    CHECK(passport_export_public_pem(handle, NULL, 0, &pem_len) == PASSPORT_ERR_BUFFER_TOO_SMALL);
+    char *pem = malloc(pem_len + 1);
+    CHECK(passport_export_public_pem(handle, (uint8_t *)pem, pem_len, &pem_len) == PASSPORT_OK);
+    pem[pem_len] = 0;
+    CHECK(strstr(pem, "-----BEGIN RSA PUBLIC KEY-----") == pem);
+    char window[1024];
+    size_t window_len = 0;
+    CHECK(passport_window_public_pem(handle, TIMESTAMP, (uint8_t *)window, sizeof(window) - 1, &window_len) == PASSPORT_OK);
+    window[window_len] = 0;
+    CHECK(strstr(pem, window) != NULL);
+    CHECK(passport_window_public_pem(handle, 1, (uint8_t *)window, sizeof(window), &window_len) == PASSPORT_ERR_OUT_OF_RANGE);
+    free(pem);
+    passport_free(handle);
+    passport_free(NULL);
+}
+
+static void test_global(void) {
+    const uint8_t key[] = "hello world";
+    CHECK(passport_init(key, sizeof(key) - 1, START, END, INTERVAL, 1024) == PASSPORT_OK);
+
+    const uint8_t data[] = "hello\0world";
+    size_t data_len = sizeof(data) - 1;
+    uint8_t sig[128];
+    size_t sig_len = 0;
+    CHECK(passport_sign_v2(data, data_len, TIMESTAMP, sig, sizeof(sig), &sig_len) == PASSPORT_OK);
+    CHECK(passport_verify_v2(data, data_len, sig, sig_len, TIMESTAMP) == PASSPORT_OK);
+    uint8_t cipher[128];
+    size_t cipher_len = 0;
+    CHECK(passport_encrypt_v2(data, data_len, TIMESTAMP, cipher, sizeof(cipher), &cipher_len) == PASSPORT_OK);
+    uint8_t plain[64];
+    size_t plain_len = 0;
+    CHECK(passport_decrypt_v2(cipher, cipher_len, TIMESTAMP, plain, sizeof(plain), &plain_len) == PASSPORT_OK);
+    CHECK(plain_len == data_len && memcmp(plain, data, data_len) == 0);
+
+    /* v1 按 C 字符串处理，缓冲区中 0 字节之前的长度即容量 */
+    const char *text = "hello world";
+    char sign_v1[129];
+    memset(sign_v1, 'x', 128);
+    sign_v1[128] = 0;
+    int res = sign(text, TIMESTAMP, sign_v1);
+    CHECK(res == 128);
+    /* 签名是确定的，这组参数得到的签名不含 0 字节 */
+    CHECK(memchr(sign_v1, 0, 128) == NULL);
+    CHECK(verify(text, sign_v1, TIMESTAMP) == 0);
+    char too_small[8] = "xxxxxxx";
+    CHECK(sign(text, TIMESTAMP, too_small) == -1);
+    CHECK(passport_last_error_code() == PASSPORT_ERR_BUFFER_TOO_SMALL);
+
+    /* 密文中有 0 字节时 v1 无法解密，重新加密直到不含 0 字节 */
+    char cipher_v1[129];
+    int found = 0;
+    for (int i = 0; i < 64 && !found; i++) {
+        memset(cipher_v1, 'x', 128);
+        cipher_v1[128] = 0;
+        res = encrypt(text, TIMESTAMP, cipher_v1);
+        CHECK(res == 128);
+        found = memchr(cipher_v1, 0, 128) == NULL;
+    }
+    CHECK(found);
+    char plain_v1[32];
+    memset(plain_v1, 'x', sizeof(plain_v1) - 1);
+    plain_v1[sizeof(plain_v1) - 1] = 0;
+    res = decrypt(cipher_v1, plain_v1, TIMESTAMP);
+    CHECK(res == (int)strlen(text) && memcmp(plain_v1, text, strlen(text)) == 0);
+    CHECK(verify(text, "bad", TIMESTAMP) == -1);
+    CHECK(passport_last_error_code() == PASSPORT_ERR_BAD_SIGNATURE);
+}
+
+int main(void) {
+    test_errors();
+    test_handle();
+    test_global();
+    if (failures > 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+    printf("all checks passed\n");
+    return 0;
+}
@@ -0,0 +1,79 @@
+//! 用系统 C 编译器编译 tests/c/test_wd_passport.c，分别链接 .a 与 .so 后运行
+#![cfg(target_os = "linux")]
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+//cargo test 不会产出 .a 与 .so，先构建本 crate；测试程序位于 target/<profile>/deps，库文件在上一级目录
+fn lib_dir() -> PathBuf {
+    let exe = std::env::current_exe().expect("无法取得测试程序路径");
+    let dir = exe
+        .parent()
+        .and_then(Path::parent)
+        .expect("无法取得 target 目录")
+        .to_path_buf();
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo
+        .args(["build", "-p", "wd_passport_ffi"])
+        .current_dir(manifest_dir());
+    if dir.ends_with("release") {
+        cargo.arg("--release");
+    }
+    let status = cargo.status().expect("无法运行 cargo");
+    assert!(status.success(), "构建 wd_passport_ffi 失败");
+    dir
+}
+
+fn compile_and_run(name: &str, link: &[&str]) {
+    let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let status = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest_dir().join("include"))
+        .arg(manifest_dir().join("tests/c/test_wd_passport.c"))
+        .arg("-o")
+        .arg(&out)
+        .args(link)
+        .status()
+        .expect("无法运行 C 编译器");
+    assert!(status.success(), "编译 C 测试程序失败");
+    let output = Command::new(&out).output().expect("无法运行 C 测试程序");
+    assert!(
+        output.status.success(),
+        "C 测试程序失败:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_c_static() {
+    let lib = lib_dir().join("libwd_passport.a");
+    compile_and_run(
+        "test_wd_passport_static",
+        &[
+            lib.to_str().expect("路径不是 utf8"),
+            "-lpthread",
+            "-ldl",
+            "-lm",
+        ],
+    );
+}
+
+#[test]
+fn test_c_shared() {
+    let dir = lib_dir();
+    let dir = dir.to_str().expect("路径不是 utf8");
+    compile_and_run(
+        "test_wd_passport_shared",
+        &[
+            &format!("-L{}", dir),
+            &format!("-Wl,-rpath,{}", dir),
+            "-lwd_passport",
+        ],
+    );
+}
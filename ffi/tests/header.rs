@@ -0,0 +1,38 @@
+//! include/wd_passport.h 必须与 clib 中的定义一致
+use std::path::PathBuf;
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn generate() -> String {
+    let config = cbindgen::Config::from_file(manifest_dir().join("cbindgen.toml"))
+        .expect("读取 cbindgen.toml 失败");
+    let mut buf = vec![];
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(manifest_dir().join("../src/clib/mod.rs"))
+        .generate()
+        .expect("生成头文件失败")
+        .write(&mut buf);
+    String::from_utf8(buf).expect("头文件不是 utf8")
+}
+
+#[test]
+fn test_header_up_to_date() {
+    let expect = std::fs::read_to_string(manifest_dir().join("include/wd_passport.h"))
+        .expect("读取头文件失败");
+    assert_eq!(
+        expect,
+        generate(),
+        "头文件与 clib 不一致，请运行 cargo test -p wd_passport_ffi export_header -- --ignored"
+    );
+}
+
+///重新生成头文件：cargo test -p wd_passport_ffi export_header -- --ignored
+#[test]
+#[ignore]
+fn export_header() {
+    std::fs::write(manifest_dir().join("include/wd_passport.h"), generate())
+        .expect("写入头文件失败");
+}
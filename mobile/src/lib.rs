@@ -0,0 +1,246 @@
+//! 只包含验签的 Kotlin / Swift 模块，接口由 uniffi 根据本 crate 生成
+//!
+//! 生成绑定：cargo build -p wd_passport_mobile --release 后
+//! cargo run -p wd_passport_mobile --bin uniffi-bindgen -- generate --library target/release/libwd_passport_mobile.so --language kotlin --out-dir out
+//!
+//! 时间戳均为 unix 秒；签名为原始字节或 base64 url safe 无填充字符串
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+use wd_passport::{Jwks, PassportVerifier};
+
+uniffi::setup_scaffolding!();
+
+///Kotlin 中为 PassportException 的子类，Swift 中为 PassportError 的 case
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum PassportError {
+    ///公钥或 jwks 无法解析，或窗口未按间隔对齐
+    InvalidKey(String),
+    ///签名等参数无法解码
+    InvalidInput(String),
+    ///时间戳不在任何窗口内
+    OutOfRange(String),
+    ///用窗口公钥加密失败，例如数据超过密钥长度
+    Encrypt(String),
+}
+
+impl Display for PassportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PassportError::InvalidKey(s) => write!(f, "invalid key: {}", s),
+            PassportError::InvalidInput(s) => write!(f, "invalid input: {}", s),
+            PassportError::OutOfRange(s) => write!(f, "out of range: {}", s),
+            PassportError::Encrypt(s) => write!(f, "encrypt error: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for PassportError {}
+
+//wd_passport::PassportError::OutOfRange 映射为 OutOfRange，其他错误交给 other
+fn passport_error(e: anyhow::Error, other: fn(String) -> PassportError) -> PassportError {
+    match e.downcast_ref::<wd_passport::PassportError>() {
+        Some(wd_passport::PassportError::OutOfRange(_)) => PassportError::OutOfRange(e.to_string()),
+        _ => other(e.to_string()),
+    }
+}
+
+///一个窗口的公钥，pem 为 PassportEntity::to_public_pem 输出的 pkcs1 公钥
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct WindowKey {
+    pub window: i64,
+    pub pem: String,
+}
+
+#[derive(Debug, uniffi::Object)]
+pub struct Verifier {
+    inner: PassportVerifier,
+}
+
+#[uniffi::export]
+impl Verifier {
+    #[uniffi::constructor]
+    pub fn from_public_pem(
+        keys: Vec<WindowKey>,
+        interval_sec: u64,
+    ) -> Result<Arc<Self>, PassportError> {
+        let pems: Vec<(i64, String)> = keys.into_iter().map(|k| (k.window, k.pem)).collect();
+        let inner =
+            PassportVerifier::from_public_pem(pems.as_slice(), Duration::from_secs(interval_sec))
+                .map_err(|e| PassportError::InvalidKey(e.to_string()))?;
+        Ok(Arc::new(Self { inner }))
+    }
+
+    ///jwks 为 passport export --format jwks 或 HTTP 服务 /keys 返回的 JSON
+    #[uniffi::constructor]
+    pub fn from_jwks(jwks: String, interval_sec: u64) -> Result<Arc<Self>, PassportError> {
+        let jwks = Jwks::from_json(jwks).map_err(|e| PassportError::InvalidKey(e.to_string()))?;
+        let inner = PassportVerifier::from_jwks(&jwks, Duration::from_secs(interval_sec))
+            .map_err(|e| PassportError::InvalidKey(e.to_string()))?;
+        Ok(Arc::new(Self { inner }))
+    }
+
+    ///全部窗口的起始时间，升序
+    pub fn windows(&self) -> Vec<i64> {
+        self.inner.windows()
+    }
+
+    ///timestamp_sec 所在窗口的起始时间，没有该窗口时返回 null / nil
+    pub fn window_of(&self, timestamp_sec: i64) -> Option<i64> {
+        self.inner.window_of(timestamp_sec)
+    }
+
+    ///timestamp_sec 签发的凭证的过期时间，即所在窗口的结束时间
+    pub fn expires_at(&self, timestamp_sec: i64) -> Option<i64> {
+        self.inner.expires_at(timestamp_sec)
+    }
+
+    ///timestamp_sec 签发的凭证在 now_sec 时是否已过期，没有该窗口时视为已过期
+    pub fn is_expired(&self, timestamp_sec: i64, now_sec: i64) -> bool {
+        match self.inner.expires_at(timestamp_sec) {
+            Some(expires) => now_sec >= expires,
+            None => true,
+        }
+    }
+
+    ///签名不匹配返回 false；时间戳不在任何窗口内时抛出 OutOfRange
+    pub fn verify(
+        &self,
+        data: Vec<u8>,
+        sign: Vec<u8>,
+        timestamp_sec: i64,
+    ) -> Result<bool, PassportError> {
+        match self
+            .inner
+            .verify_sha256(data.as_slice(), sign.as_slice(), timestamp_sec)
+        {
+            Ok(_) => Ok(true),
+            Err(e) => match e.downcast_ref::<wd_passport::PassportError>() {
+                Some(wd_passport::PassportError::BadSignature) => Ok(false),
+                _ => Err(passport_error(e, PassportError::InvalidInput)),
+            },
+        }
+    }
+
+    ///sign 为 base64 url safe 无填充编码，无法解码时抛出 InvalidInput
+    pub fn verify_base64(
+        &self,
+        data: Vec<u8>,
+        sign: String,
+        timestamp_sec: i64,
+    ) -> Result<bool, PassportError> {
+        let sign =
+            wd_passport::decode(sign).map_err(|e| PassportError::InvalidInput(e.to_string()))?;
+        self.verify(data, sign, timestamp_sec)
+    }
+}
+
+#[cfg(feature = "encrypt")]
+#[uniffi::export]
+impl Verifier {
+    ///用 timestamp_sec 所在窗口的公钥加密，只有持有该窗口私钥的服务端可以解密
+    pub fn encrypt(&self, data: Vec<u8>, timestamp_sec: i64) -> Result<Vec<u8>, PassportError> {
+        self.inner
+            .encrypt(data.as_slice(), timestamp_sec)
+            .map_err(|e| passport_error(e, PassportError::Encrypt))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INTERVAL: u64 = 60 * 60 * 24 * 30;
+    const TIMESTAMP: i64 = 1679919556;
+
+    fn fixture() -> serde_json::Value {
+        serde_json::from_str(include_str!("../../testdata/fixture.json")).expect("读取测试数据失败")
+    }
+
+    fn verifiers() -> (Arc<Verifier>, Arc<Verifier>) {
+        let fixture = fixture();
+        let pems: Vec<(i64, String)> =
+            serde_json::from_value(fixture["public_pem"].clone()).expect("读取 pem 失败");
+        let keys = pems
+            .into_iter()
+            .map(|(window, pem)| WindowKey { window, pem })
+            .collect();
+        let from_pem = Verifier::from_public_pem(keys, INTERVAL).expect("由 pem 构建验签器失败");
+        let from_jwks = Verifier::from_jwks(fixture["jwks"].to_string(), INTERVAL)
+            .expect("由 jwks 构建验签器失败");
+        (from_pem, from_jwks)
+    }
+
+    #[test]
+    fn test_verify() {
+        let (from_pem, from_jwks) = verifiers();
+        assert_eq!(from_pem.inner, from_jwks.inner);
+        assert_eq!(from_pem.windows().len(), 3);
+        let encoded = fixture()["signature"]
+            .as_str()
+            .expect("缺少签名")
+            .to_string();
+        let sign = wd_passport::decode(encoded.as_str()).expect("签名解码失败");
+        assert_eq!(
+            from_pem.verify(b"hello world".to_vec(), sign.clone(), TIMESTAMP),
+            Ok(true)
+        );
+        assert_eq!(
+            from_pem.verify_base64(b"hello world".to_vec(), encoded, TIMESTAMP),
+            Ok(true)
+        );
+        assert_eq!(
+            from_pem.verify(b"hello".to_vec(), sign.clone(), TIMESTAMP),
+            Ok(false)
+        );
+        assert!(matches!(
+            from_pem.verify(b"hello world".to_vec(), sign, 1),
+            Err(PassportError::OutOfRange(_))
+        ));
+        assert!(matches!(
+            from_pem.verify_base64(b"hello world".to_vec(), "!".to_string(), TIMESTAMP),
+            Err(PassportError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            Verifier::from_jwks("not json".to_string(), INTERVAL),
+            Err(PassportError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_expiry() {
+        let (verifier, _) = verifiers();
+        let window = verifier.window_of(TIMESTAMP).expect("缺少窗口");
+        let expires = verifier.expires_at(TIMESTAMP).expect("缺少过期时间");
+        assert_eq!(expires, window + INTERVAL as i64);
+        assert!(!verifier.is_expired(TIMESTAMP, TIMESTAMP));
+        assert!(!verifier.is_expired(TIMESTAMP, expires - 1));
+        assert!(verifier.is_expired(TIMESTAMP, expires));
+        assert!(verifier.is_expired(1, 1));
+        assert_eq!(verifier.expires_at(1), None);
+    }
+
+    #[cfg(feature = "encrypt")]
+    #[test]
+    fn test_encrypt() {
+        let (verifier, _) = verifiers();
+        let a = verifier
+            .encrypt(b"hello world".to_vec(), TIMESTAMP)
+            .expect("加密失败");
+        let b = verifier
+            .encrypt(b"hello world".to_vec(), TIMESTAMP)
+            .expect("加密失败");
+        //1024 位密钥，pkcs1 v1.5 填充每次随机
+        assert_eq!(a.len(), 128);
+        assert_ne!(a, b);
+        assert!(matches!(
+            verifier.encrypt(b"hello world".to_vec(), 1),
+            Err(PassportError::OutOfRange(_))
+        ));
+        assert!(matches!(
+            verifier.encrypt(vec![0u8; 200], TIMESTAMP),
+            Err(PassportError::Encrypt(_))
+        ));
+    }
+}
@@ -0,0 +1,100 @@
+//! 用 uniffi-bindgen 从构建出的动态库生成 Kotlin 与 Swift 绑定，并在 JVM 上运行 Kotlin 测试脚本
+#![cfg(target_os = "linux")]
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+//cargo test 不保证产出 cdylib，先构建本 crate；测试程序位于 target/<profile>/deps，库文件在上一级目录
+fn cdylib() -> PathBuf {
+    let exe = std::env::current_exe().expect("无法取得测试程序路径");
+    let dir = exe
+        .parent()
+        .and_then(Path::parent)
+        .expect("无法取得 target 目录")
+        .to_path_buf();
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo
+        .args(["build", "-p", "wd_passport_mobile", "--lib"])
+        .current_dir(manifest_dir());
+    if dir.ends_with("release") {
+        cargo.arg("--release");
+    }
+    let status = cargo.status().expect("无法运行 cargo");
+    assert!(status.success(), "构建 wd_passport_mobile 失败");
+    dir.join("libwd_passport_mobile.so")
+}
+
+fn generate(language: &str) -> PathBuf {
+    let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("bindings_{}", language));
+    let status = Command::new(env!("CARGO_BIN_EXE_uniffi-bindgen"))
+        .arg("generate")
+        .arg("--library")
+        .arg(cdylib())
+        .args(["--language", language, "--out-dir"])
+        .arg(&out)
+        .current_dir(manifest_dir())
+        .status()
+        .expect("无法运行 uniffi-bindgen");
+    assert!(status.success(), "生成 {} 绑定失败", language);
+    out
+}
+
+fn read(path: PathBuf) -> String {
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("读取 {:?} 失败:{}", path, e))
+}
+
+#[test]
+fn test_kotlin_bindings() {
+    let source = read(generate("kotlin").join("wd/passport/wd_passport_mobile.kt"));
+    for name in [
+        "package wd.passport",
+        "open class Verifier",
+        "fun `fromPublicPem`",
+        "fun `fromJwks`",
+        "fun `verifyBase64`",
+        "fun `isExpired`",
+        "data class WindowKey",
+        "class OutOfRange",
+    ] {
+        assert!(source.contains(name), "Kotlin 绑定缺少 {}", name);
+    }
+    if cfg!(feature = "encrypt") {
+        assert!(source.contains("fun `encrypt`"), "Kotlin 绑定缺少 encrypt");
+    }
+}
+
+#[test]
+fn test_swift_bindings() {
+    let out = generate("swift");
+    let source = read(out.join("WdPassport.swift"));
+    for name in [
+        "open class Verifier",
+        "func verifyBase64(",
+        "func isExpired(",
+        "public struct WindowKey",
+        "case OutOfRange",
+    ] {
+        assert!(source.contains(name), "Swift 绑定缺少 {}", name);
+    }
+    if cfg!(feature = "encrypt") {
+        assert!(source.contains("func encrypt("), "Swift 绑定缺少 encrypt");
+    }
+    assert!(out.join("WdPassportFFI.h").exists(), "缺少 Swift 头文件");
+}
+
+//需要 PATH 中有 kotlinc，CLASSPATH 中有 jna 的 jar：cargo test -p wd_passport_mobile -- --ignored
+//CI 的 kotlin job 会安装二者并运行本测试
+#[test]
+#[ignore = "需要 kotlinc 与 jna"]
+fn test_kotlin_script() {
+    cdylib();
+    uniffi::kotlin_test::run_test(
+        env!("CARGO_TARGET_TMPDIR"),
+        env!("CARGO_PKG_NAME"),
+        "tests/bindings/test_verifier.kts",
+    )
+    .expect("Kotlin 测试失败");
+}
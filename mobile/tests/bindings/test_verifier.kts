@@ -0,0 +1,49 @@
+// uniffi 生成的 Kotlin 绑定测试，由 tests/bindings.rs 中的 test_kotlin_script 运行
+import wd.passport.*
+
+// testdata/fixture.json 中的数据，见 testdata/README.md
+val interval = 2592000UL
+val timestamp = 1679919556L
+val jwks = """{"keys":[{"kty":"RSA","use":"sig","alg":"RS256","kid":"a3fd234a2669b2b4b6a3cd3c912be96f13b3e941","n":"vvW-a3wkAZDisCo9Od6J6tr2B_tu_olIiiK0jctxcB2esclLH5Sizf8HVCWYIWeKtDK1_gOFCKtkMz2gjxAfDFHxPdnTYluCNKCeCPSnrQmqDUpog_KK3OqhFaHPzV6osAkSNxoEf4t189fgAd_PwWr6H0hrandkifu0rmf1JOk","e":"AQAB","window":1674432000},{"kty":"RSA","use":"sig","alg":"RS256","kid":"ec074a71b46e8d08ac92d1a6bd7450111fe6110c","n":"t0NubuNXpj4RYfsKlqpcimKMTtLfJjDy9vriz31qv9FUGRj_qRpw10cI83cKnh9g6XOEmBGH1XTvqJLbruu1_M_SPC7zkr627Dm9gSBpgEFiIw__fC7_nYeJ-fAuywEL2dtePPpATXtoi8MMG4E1KXFcRJaTTTw4kl2K91Tohns","e":"AQAB","window":1677024000},{"kty":"RSA","use":"sig","alg":"RS256","kid":"8da5cd22666b312701fa74c7f2c3f513e45caa42","n":"0WPocN9WXuXwRlG-zJzqU1_LxNiehJi7GrRC3fhjZ5q1MMy1LEt5Q7ulSQVWEibWAIB9dp3Erxb4yy2u2MX5XageixeWd1TFX81qq0Zqb9wTqRX_bMCnH45aPul3OYhJeeq7cPaIZbUxYsMnhj0pDY6lpeetawcF2We1avYE32s","e":"AQAB","window":1679616000}]}"""
+val signature = "dzsEBCDIl9SAX4DOUPgFilAPQCriA-N-qlTWl_KIBTcyqrp2nzDIrrgLOftbGvC9lL0tErdzTalG2dSqbZN8kpLN-NY2kP8q1mGPTH3Bl_sxT_WdAnq6sh3JyW570bNESzDJYuo6EyqxTeaUvbMZescr6DwUvoQ1-EhlqiBRhCQ"
+val data = "hello world".toByteArray()
+
+val verifier = Verifier.fromJwks(jwks, interval)
+assert(verifier.windows() == listOf(1674432000L, 1677024000L, 1679616000L))
+assert(verifier.windowOf(timestamp) == 1679616000L)
+assert(verifier.windowOf(1L) == null)
+
+// 验签
+assert(verifier.verifyBase64(data, signature, timestamp))
+assert(!verifier.verifyBase64("hello".toByteArray(), signature, timestamp))
+val sign = java.util.Base64.getUrlDecoder().decode(signature)
+assert(verifier.verify(data, sign, timestamp))
+try {
+    verifier.verify(data, sign, 1L)
+    throw RuntimeException("应当抛出 OutOfRange")
+} catch (e: PassportException.OutOfRange) {
+}
+try {
+    verifier.verifyBase64(data, "!", timestamp)
+    throw RuntimeException("应当抛出 InvalidInput")
+} catch (e: PassportException.InvalidInput) {
+}
+
+// 凭证过期
+val expires = verifier.expiresAt(timestamp)!!
+assert(expires == 1679616000L + interval.toLong())
+assert(!verifier.isExpired(timestamp, expires - 1))
+assert(verifier.isExpired(timestamp, expires))
+assert(verifier.isExpired(1L, 1L))
+
+// 用窗口公钥加密
+val cipher = verifier.encrypt(data, timestamp)
+assert(cipher.size == 128)
+assert(!cipher.contentEquals(verifier.encrypt(data, timestamp)))
+
+try {
+    Verifier.fromJwks("not json", interval)
+    throw RuntimeException("应当抛出 InvalidKey")
+} catch (e: PassportException.InvalidKey) {
+}
+verifier.close()
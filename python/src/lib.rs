@@ -0,0 +1,205 @@
+//! wd_passport 的 Python 模块，数据均为 bytes，错误映射为 PassportError 的子类
+//!
+//! 构建 wheel：cd python && maturin build --release
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+use std::time::Duration;
+use wd_passport::PassportEntity;
+
+create_exception!(
+    wd_passport,
+    PassportError,
+    PyException,
+    "wd_passport 的所有错误"
+);
+create_exception!(
+    wd_passport,
+    OutOfRangeError,
+    PassportError,
+    "时间戳不在任何窗口内"
+);
+create_exception!(
+    wd_passport,
+    BadSignatureError,
+    PassportError,
+    "签名与数据不匹配"
+);
+create_exception!(
+    wd_passport,
+    DecryptError,
+    PassportError,
+    "密文无法用该窗口的私钥解密"
+);
+
+//按 wd_passport::PassportError 映射到对应的子类
+fn passport_error(e: anyhow::Error) -> PyErr {
+    match e.downcast_ref::<wd_passport::PassportError>() {
+        Some(wd_passport::PassportError::OutOfRange(_)) => OutOfRangeError::new_err(e.to_string()),
+        Some(wd_passport::PassportError::BadSignature) => BadSignatureError::new_err(e.to_string()),
+        Some(wd_passport::PassportError::Decrypt) => DecryptError::new_err(e.to_string()),
+        _ => PassportError::new_err(e.to_string()),
+    }
+}
+
+//key 可以是 bytes 或 str，str 按 utf8 编码
+fn key_bytes(key: &Bound<'_, PyAny>) -> PyResult<Vec<u8>> {
+    if let Ok(s) = key.downcast::<PyString>() {
+        return Ok(s.to_str()?.as_bytes().to_vec());
+    }
+    Ok(key.downcast::<PyBytes>()?.as_bytes().to_vec())
+}
+
+///按时间窗口轮换的证书，start/end/timestamp 均为 unix 秒
+#[pyclass(name = "PassportEntity", module = "wd_passport", frozen)]
+struct PyPassportEntity {
+    entity: PassportEntity,
+}
+
+#[pymethods]
+impl PyPassportEntity {
+    #[new]
+    #[pyo3(signature = (key, start, end, interval, bits = 2048))]
+    fn new(
+        py: Python<'_>,
+        key: &Bound<'_, PyAny>,
+        start: i64,
+        end: i64,
+        interval: u64,
+        bits: usize,
+    ) -> PyResult<Self> {
+        let key = key_bytes(key)?;
+        let range = match (
+            chrono::DateTime::from_timestamp(start, 0),
+            chrono::DateTime::from_timestamp(end, 0),
+        ) {
+            (Some(s), Some(e)) => (s, e),
+            _ => {
+                return Err(PassportError::new_err(format!(
+                    "invalid range[{},{}]",
+                    start, end
+                )))
+            }
+        };
+        //生成密钥耗时较长，期间释放 GIL
+        let entity = py
+            .allow_threads(|| PassportEntity::new(key, range, Duration::from_secs(interval), bits))
+            .map_err(passport_error)?;
+        Ok(Self { entity })
+    }
+
+    fn sign_sha256<'py>(
+        &self,
+        py: Python<'py>,
+        data: &[u8],
+        timestamp: i64,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let sign = py
+            .allow_threads(|| self.entity.sign_sha256(data, timestamp))
+            .map_err(passport_error)?;
+        Ok(PyBytes::new(py, sign.as_slice()))
+    }
+
+    ///验签失败抛出 BadSignatureError
+    fn verify_sha256(
+        &self,
+        py: Python<'_>,
+        data: &[u8],
+        sign: &[u8],
+        timestamp: i64,
+    ) -> PyResult<()> {
+        py.allow_threads(|| self.entity.verify_sha256(data, sign, timestamp))
+            .map_err(passport_error)
+    }
+
+    fn encrypt<'py>(
+        &self,
+        py: Python<'py>,
+        data: &[u8],
+        timestamp: i64,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let buf = py
+            .allow_threads(|| self.entity.encrypt(data, timestamp))
+            .map_err(passport_error)?;
+        Ok(PyBytes::new(py, buf.as_slice()))
+    }
+
+    fn decrypt<'py>(
+        &self,
+        py: Python<'py>,
+        data: &[u8],
+        timestamp: i64,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let buf = py
+            .allow_threads(|| self.entity.decrypt(data, timestamp))
+            .map_err(passport_error)?;
+        Ok(PyBytes::new(py, buf.as_slice()))
+    }
+
+    ///[(窗口起始时间, pkcs1 公钥 PEM)]，按窗口排序
+    fn to_public_pem(&self) -> Vec<(i64, String)> {
+        self.entity.to_public_pem()
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "wd_passport")]
+fn wd_passport_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add_class::<PyPassportEntity>()?;
+    m.add("PassportError", py.get_type::<PassportError>())?;
+    m.add("OutOfRangeError", py.get_type::<OutOfRangeError>())?;
+    m.add("BadSignatureError", py.get_type::<BadSignatureError>())?;
+    m.add("DecryptError", py.get_type::<DecryptError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::CString;
+
+    //在嵌入的解释器中运行 tests/test_wd_passport.py，与 wheel 安装后的测试相同
+    #[test]
+    fn test_python_module() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "wd_passport").expect("创建模块失败");
+            wd_passport_py(&module).expect("初始化模块失败");
+            py.import("sys")
+                .and_then(|sys| sys.getattr("modules"))
+                .and_then(|modules| modules.set_item("wd_passport", &module))
+                .expect("注册模块失败");
+            let source = CString::new(include_str!("../tests/test_wd_passport.py"))
+                .expect("测试脚本包含 0 字节");
+            let tests = PyModule::from_code(
+                py,
+                source.as_c_str(),
+                c"test_wd_passport.py",
+                c"test_wd_passport",
+            )
+            .expect("加载测试脚本失败");
+            let unittest = py.import("unittest").expect("导入 unittest 失败");
+            let suite = unittest
+                .getattr("defaultTestLoader")
+                .and_then(|loader| loader.call_method1("loadTestsFromModule", (tests,)))
+                .expect("加载测试失败");
+            let result = unittest
+                .call_method0("TextTestRunner")
+                .and_then(|runner| runner.call_method1("run", (suite,)))
+                .expect("运行测试失败");
+            let run: usize = result
+                .getattr("testsRun")
+                .and_then(|n| n.extract())
+                .expect("读取测试数量失败");
+            assert!(run > 0, "没有找到 Python 测试");
+            let ok: bool = result
+                .call_method0("wasSuccessful")
+                .and_then(|ok| ok.extract())
+                .expect("读取测试结果失败");
+            assert!(ok, "Python 测试失败");
+        });
+    }
+}
@@ -0,0 +1,62 @@
+"""wd_passport Python 模块测试：python -m unittest discover -s tests"""
+import unittest
+
+import wd_passport
+
+START = 1675641600
+END = 1680739200
+INTERVAL = 60 * 60 * 24 * 30
+TIMESTAMP = 1679919556
+
+
+class PassportEntityTest(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.entity = wd_passport.PassportEntity(b"hello world", START, END, INTERVAL, 1024)
+
+    def test_sign_verify(self):
+        data = b"hello\0world"
+        sign = self.entity.sign_sha256(data, TIMESTAMP)
+        self.assertIsInstance(sign, bytes)
+        self.assertEqual(len(sign), 128)
+        self.entity.verify_sha256(data, sign, TIMESTAMP)
+        with self.assertRaises(wd_passport.BadSignatureError):
+            self.entity.verify_sha256(b"hello", sign, TIMESTAMP)
+        # str key 与相同的 bytes key 生成相同的证书
+        other = wd_passport.PassportEntity("hello world", START, END, INTERVAL, 1024)
+        self.assertEqual(other.sign_sha256(data, TIMESTAMP), sign)
+        # 以绑定签名标记开头的数据不能普通签名
+        with self.assertRaises(wd_passport.PassportError):
+            self.entity.sign_sha256(b"wd_passport.bound.v1\0" + data, TIMESTAMP)
+
+    def test_encrypt_decrypt(self):
+        data = b"\0\x01binary\0"
+        cipher = self.entity.encrypt(data, TIMESTAMP)
+        self.assertEqual(self.entity.decrypt(cipher, TIMESTAMP), data)
+        with self.assertRaises(wd_passport.DecryptError):
+            self.entity.decrypt(b"not a ciphertext", TIMESTAMP)
+
+    def test_out_of_range(self):
+        with self.assertRaises(wd_passport.OutOfRangeError) as ctx:
+            self.entity.sign_sha256(b"data", 1)
+        self.assertIsInstance(ctx.exception, wd_passport.PassportError)
+        with self.assertRaises(wd_passport.OutOfRangeError):
+            self.entity.verify_sha256(b"data", b"sign", END + INTERVAL)
+
+    def test_to_public_pem(self):
+        pems = self.entity.to_public_pem()
+        self.assertEqual(len(pems), 3)
+        windows = [w for w, _ in pems]
+        self.assertEqual(windows, sorted(windows))
+        for _, pem in pems:
+            self.assertTrue(pem.startswith("-----BEGIN RSA PUBLIC KEY-----"))
+
+    def test_invalid(self):
+        with self.assertRaises(wd_passport.PassportError):
+            wd_passport.PassportEntity(b"key", START, END, 0, 1024)
+        with self.assertRaises(TypeError):
+            wd_passport.PassportEntity(1, START, END, INTERVAL, 1024)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,69 @@
+use crate::agent::protocol::{read_response, verify_payload, write_request, AgentOp, Request};
+use crate::common::PFErr;
+use std::io::{BufReader, BufWriter};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::Mutex;
+
+struct Connection {
+    reader: BufReader<UnixStream>,
+    writer: BufWriter<UnixStream>,
+}
+
+///PassportAgent 的客户端，签名等方法与 PassportEntity 一致，但 key 只存在于 agent 进程中
+pub struct AgentClient {
+    conn: Mutex<Connection>,
+}
+
+impl AgentClient {
+    pub fn connect<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let stream = UnixStream::connect(path)?;
+        let conn = Connection {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: BufWriter::new(stream),
+        };
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn call(&self, op: AgentOp, timestamp: i64, payload: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        let mut conn = match self.conn.lock() {
+            Ok(o) => o,
+            Err(e) => return anyhow::anyhow!("agent connection lock error:{}", e).err(),
+        };
+        let req = Request {
+            op,
+            timestamp,
+            payload,
+        };
+        write_request(&mut conn.writer, &req)?;
+        read_response(&mut conn.reader)
+    }
+
+    #[cfg(feature = "encrypt")]
+    pub fn encrypt<D: AsRef<[u8]>>(&self, data: D, timestamp_sec: i64) -> anyhow::Result<Vec<u8>> {
+        self.call(AgentOp::Encrypt, timestamp_sec, data.as_ref().to_vec())
+    }
+    #[cfg(feature = "encrypt")]
+    pub fn decrypt<D: AsRef<[u8]>>(&self, data: D, timestamp_sec: i64) -> anyhow::Result<Vec<u8>> {
+        self.call(AgentOp::Decrypt, timestamp_sec, data.as_ref().to_vec())
+    }
+    pub fn sign_sha256<D: AsRef<[u8]>>(
+        &self,
+        data: D,
+        timestamp_sec: i64,
+    ) -> anyhow::Result<Vec<u8>> {
+        self.call(AgentOp::Sign, timestamp_sec, data.as_ref().to_vec())
+    }
+    pub fn verify_sha256<D: AsRef<[u8]>>(
+        &self,
+        data: D,
+        sign: D,
+        timestamp_sec: i64,
+    ) -> anyhow::Result<()> {
+        let payload = verify_payload(data.as_ref(), sign.as_ref());
+        self.call(AgentOp::Verify, timestamp_sec, payload)?;
+        Ok(())
+    }
+}
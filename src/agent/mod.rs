@@ -0,0 +1,218 @@
+mod client;
+mod protocol;
+mod server;
+
+pub use client::AgentClient;
+pub use protocol::{AgentError, AgentOp, AgentStatus, MAX_PAYLOAD};
+pub use server::{AgentPolicy, PassportAgent, DEFAULT_MAX_CONNECTIONS, DEFAULT_TIMEOUT};
+
+#[cfg(test)]
+mod test {
+    use super::{AgentClient, AgentError, AgentOp, AgentPolicy, AgentStatus, PassportAgent};
+    use crate::app::PassportEntity;
+    use chrono::NaiveDateTime;
+    use std::os::unix::net::UnixStream;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn entity() -> PassportEntity {
+        let start = NaiveDateTime::parse_from_str("2023-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("起始时间生成错误")
+            .and_utc();
+        let end = NaiveDateTime::parse_from_str("2023-04-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("截止时间生成错误")
+            .and_utc();
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        PassportEntity::new("hello world", (start, end), interval, 1024).expect("证书生成失败")
+    }
+
+    fn start_agent(name: &str, policy: AgentPolicy) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "wd_passport_agent_{}_{}.sock",
+            std::process::id(),
+            name
+        ));
+        let agent = Arc::new(PassportAgent::new(entity(), policy));
+        let listener = agent.bind(&path).expect("监听 socket 失败");
+        std::thread::spawn(move || agent.serve(listener));
+        path
+    }
+
+    fn status(e: anyhow::Error) -> AgentStatus {
+        e.downcast_ref::<AgentError>()
+            .expect("不是 agent 错误")
+            .status
+    }
+
+    #[test]
+    fn test_agent() {
+        let path = start_agent("all", AgentPolicy::new().allow_current_user());
+        let client = AgentClient::connect(&path).expect("连接 agent 失败");
+        let expect = entity()
+            .sign_sha256("hello world", 1679919556)
+            .expect("签名错误");
+
+        //同一连接上连续发送多个请求
+        let sign = client
+            .sign_sha256("hello world", 1679919556)
+            .expect("agent 签名失败");
+        assert_eq!(sign, expect);
+        client
+            .verify_sha256("hello world".as_bytes(), sign.as_slice(), 1679919556)
+            .expect("agent 验签失败");
+        let e = client
+            .verify_sha256("hello".as_bytes(), sign.as_slice(), 1679919556)
+            .unwrap_err();
+        assert_eq!(status(e), AgentStatus::BadSignature);
+        let e = client.sign_sha256("hello world", 1000).unwrap_err();
+        assert_eq!(status(e), AgentStatus::OutOfRange);
+        //agent 同样拒绝普通签名带绑定标记的数据
+        let e = client
+            .sign_sha256(crate::BOUND_SIGN_TAG, 1679919556)
+            .unwrap_err();
+        assert_eq!(status(e), AgentStatus::Error);
+        #[cfg(feature = "encrypt")]
+        {
+            let cipher = client
+                .encrypt("hello world", 1679919556)
+                .expect("agent 加密失败");
+            let plain = client.decrypt(cipher, 1679919556).expect("agent 解密失败");
+            assert_eq!(plain, b"hello world");
+        }
+        let e = client.sign_sha256(vec![0u8; super::MAX_PAYLOAD + 1], 1679919556);
+        assert!(e.is_err());
+        //超长请求没有写出任何数据，连接仍然可用
+        assert_eq!(
+            client
+                .sign_sha256("hello world", 1679919556)
+                .expect("agent 签名失败"),
+            expect
+        );
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path)
+            .expect("socket 不存在")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_agent_bind() {
+        let agent = PassportAgent::new(entity(), AgentPolicy::new().allow_current_user());
+        let dir = std::env::temp_dir();
+        //不是 socket 的文件或符号链接不会被删除
+        let file = dir.join(format!("wd_passport_agent_{}_file", std::process::id()));
+        std::fs::write(&file, b"keep").expect("写入文件失败");
+        assert!(agent.bind(&file).is_err());
+        let link = dir.join(format!("wd_passport_agent_{}_link", std::process::id()));
+        let _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(&file, &link).expect("创建符号链接失败");
+        assert!(agent.bind(&link).is_err());
+        assert_eq!(std::fs::read(&file).expect("文件被删除"), b"keep");
+        assert!(std::fs::symlink_metadata(&link).is_ok(), "符号链接被删除");
+        let _ = std::fs::remove_file(&link);
+        let _ = std::fs::remove_file(&file);
+
+        //已存在的 socket 会被替换
+        let path = dir.join(format!(
+            "wd_passport_agent_{}_rebind.sock",
+            std::process::id()
+        ));
+        drop(agent.bind(&path).expect("监听 socket 失败"));
+        let _listener = agent.bind(&path).expect("重新监听 socket 失败");
+        assert!(UnixStream::connect(&path).is_ok());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_agent_max_connections() {
+        let path =
+            std::env::temp_dir().join(format!("wd_passport_agent_{}_max.sock", std::process::id()));
+        let agent = Arc::new(
+            PassportAgent::new(entity(), AgentPolicy::new().allow_current_user())
+                .set_max_connections(1),
+        );
+        let listener = agent.bind(&path).expect("监听 socket 失败");
+        std::thread::spawn(move || agent.serve(listener));
+
+        let first = AgentClient::connect(&path).expect("连接 agent 失败");
+        first
+            .sign_sha256("hello world", 1679919556)
+            .expect("agent 签名失败");
+        let second = AgentClient::connect(&path).expect("连接 agent 失败");
+        assert!(second.sign_sha256("hello world", 1679919556).is_err());
+        //第一个连接关闭后可以再次连接
+        drop(first);
+        let mut retry = 0;
+        loop {
+            let client = AgentClient::connect(&path).expect("连接 agent 失败");
+            if client.sign_sha256("hello world", 1679919556).is_ok() {
+                break;
+            }
+            retry += 1;
+            assert!(retry < 50, "连接数没有释放");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_agent_timeout() {
+        let path = std::env::temp_dir().join(format!(
+            "wd_passport_agent_{}_timeout.sock",
+            std::process::id()
+        ));
+        let agent = Arc::new(
+            PassportAgent::new(entity(), AgentPolicy::new().allow_current_user())
+                .set_max_connections(1)
+                .set_timeout(Some(Duration::from_millis(200))),
+        );
+        let listener = agent.bind(&path).expect("监听 socket 失败");
+        std::thread::spawn(move || agent.serve(listener));
+
+        //空闲的连接超时后被关闭，不再占用连接数
+        let idle = UnixStream::connect(&path).expect("连接 agent 失败");
+        std::thread::sleep(Duration::from_millis(500));
+        let client = AgentClient::connect(&path).expect("连接 agent 失败");
+        client
+            .sign_sha256("hello world", 1679919556)
+            .expect("agent 签名失败");
+        drop(idle);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_agent_policy() {
+        let uid = unsafe { libc::getuid() };
+        let path = start_agent("verify", AgentPolicy::new().allow(uid, &[AgentOp::Verify]));
+        let client = AgentClient::connect(&path).expect("连接 agent 失败");
+        let sign = entity()
+            .sign_sha256("hello world", 1679919556)
+            .expect("签名错误");
+        client
+            .verify_sha256("hello world".as_bytes(), sign.as_slice(), 1679919556)
+            .expect("agent 验签失败");
+        let e = client.sign_sha256("hello world", 1679919556).unwrap_err();
+        assert_eq!(status(e), AgentStatus::Denied);
+        #[cfg(feature = "encrypt")]
+        {
+            let e = client.decrypt("hello world", 1679919556).unwrap_err();
+            assert_eq!(status(e), AgentStatus::Denied);
+        }
+        let _ = std::fs::remove_file(path);
+
+        //不在白名单中的 uid，连接在 accept 后直接关闭
+        let path = start_agent("other", AgentPolicy::new().allow_all(uid.wrapping_add(1)));
+        let client = AgentClient::connect(&path).expect("连接 agent 失败");
+        let e = client
+            .verify_sha256("hello world".as_bytes(), sign.as_slice(), 1679919556)
+            .unwrap_err();
+        assert!(e.downcast_ref::<AgentError>().is_none(), "连接应被直接关闭");
+        let _ = std::fs::remove_file(path);
+
+        assert_eq!(AgentOp::from_name("sign"), Some(AgentOp::Sign));
+        assert_eq!(AgentOp::from_name("delete"), None);
+    }
+}
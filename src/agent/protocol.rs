@@ -0,0 +1,205 @@
+//! agent 的二进制协议，所有整数均为大端
+//!
+//! 请求：magic "WDPA" | version u8 | op u8 | timestamp i64 | len u32 | payload
+//! 响应：status u8 | len u32 | payload，status 非 Ok 时 payload 为 utf8 错误信息
+//! Verify 的 payload 为 data len u32 | data | signature，其余操作的 payload 即数据本身
+use crate::common::{PFErr, PFOk};
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+
+pub(crate) const MAGIC: &[u8; 4] = b"WDPA";
+pub(crate) const VERSION: u8 = 1;
+///单个请求或响应 payload 的上限
+pub const MAX_PAYLOAD: usize = 1024 * 1024;
+
+///agent 支持的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AgentOp {
+    Sign = 1,
+    Decrypt = 2,
+    Verify = 3,
+    Encrypt = 4,
+}
+
+impl AgentOp {
+    pub const ALL: [AgentOp; 4] = [
+        AgentOp::Sign,
+        AgentOp::Decrypt,
+        AgentOp::Verify,
+        AgentOp::Encrypt,
+    ];
+    fn from_u8(op: u8) -> Option<Self> {
+        AgentOp::ALL.into_iter().find(|o| *o as u8 == op)
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            AgentOp::Sign => "sign",
+            AgentOp::Decrypt => "decrypt",
+            AgentOp::Verify => "verify",
+            AgentOp::Encrypt => "encrypt",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        AgentOp::ALL.into_iter().find(|o| o.name() == name)
+    }
+}
+
+///响应状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentStatus {
+    Ok = 0,
+    Error = 1,
+    ///peer uid 不在白名单或无权执行该操作
+    Denied = 2,
+    ///时间戳不在任何窗口内
+    OutOfRange = 3,
+    BadSignature = 4,
+}
+
+impl AgentStatus {
+    const ALL: [AgentStatus; 5] = [
+        AgentStatus::Ok,
+        AgentStatus::Error,
+        AgentStatus::Denied,
+        AgentStatus::OutOfRange,
+        AgentStatus::BadSignature,
+    ];
+    fn from_u8(status: u8) -> Self {
+        AgentStatus::ALL
+            .into_iter()
+            .find(|s| *s as u8 == status)
+            .unwrap_or(AgentStatus::Error)
+    }
+}
+
+///agent 返回的错误，可以通过 anyhow::Error::downcast_ref 取得
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentError {
+    pub status: AgentStatus,
+    pub message: String,
+}
+
+impl Display for AgentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "agent {:?}: {}", self.status, self.message)
+    }
+}
+
+impl std::error::Error for AgentError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Request {
+    pub op: AgentOp,
+    pub timestamp: i64,
+    pub payload: Vec<u8>,
+}
+
+fn read_payload<R: Read + ?Sized>(r: &mut R) -> anyhow::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    r.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_PAYLOAD {
+        return anyhow::anyhow!("agent payload too large[{}]", len).err();
+    }
+    let mut payload = vec![0u8; len];
+    r.read_exact(payload.as_mut_slice())?;
+    Ok(payload)
+}
+
+fn write_payload<W: Write + ?Sized>(w: &mut W, payload: &[u8]) -> anyhow::Result<()> {
+    if payload.len() > MAX_PAYLOAD {
+        return anyhow::anyhow!("agent payload too large[{}]", payload.len()).err();
+    }
+    w.write_all(&(payload.len() as u32).to_be_bytes())?;
+    w.write_all(payload)?;
+    Ok(())
+}
+
+pub(crate) fn write_request<W: Write + ?Sized>(w: &mut W, req: &Request) -> anyhow::Result<()> {
+    //先检查长度，避免只写出请求头导致连接上的数据错位
+    if req.payload.len() > MAX_PAYLOAD {
+        return anyhow::anyhow!("agent payload too large[{}]", req.payload.len()).err();
+    }
+    let mut head = Vec::with_capacity(14);
+    head.extend_from_slice(MAGIC);
+    head.push(VERSION);
+    head.push(req.op as u8);
+    head.extend_from_slice(&req.timestamp.to_be_bytes());
+    w.write_all(head.as_slice())?;
+    write_payload(w, req.payload.as_slice())?;
+    w.flush()?;
+    Ok(())
+}
+
+///读取一个请求，连接正常关闭时返回 None
+pub(crate) fn read_request<R: Read + ?Sized>(r: &mut R) -> anyhow::Result<Option<Request>> {
+    let mut head = [0u8; 14];
+    match r.read_exact(&mut head[..1]) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    r.read_exact(&mut head[1..])?;
+    if &head[..4] != MAGIC {
+        return anyhow::anyhow!("agent request bad magic").err();
+    }
+    if head[4] != VERSION {
+        return anyhow::anyhow!("agent protocol version[{}] not supported", head[4]).err();
+    }
+    let op = match AgentOp::from_u8(head[5]) {
+        Some(s) => s,
+        None => return anyhow::anyhow!("agent op[{}] not supported", head[5]).err(),
+    };
+    let mut timestamp = [0u8; 8];
+    timestamp.copy_from_slice(&head[6..14]);
+    Some(Request {
+        op,
+        timestamp: i64::from_be_bytes(timestamp),
+        payload: read_payload(r)?,
+    })
+    .ok()
+}
+
+pub(crate) fn write_response<W: Write + ?Sized>(
+    w: &mut W,
+    status: AgentStatus,
+    payload: &[u8],
+) -> anyhow::Result<()> {
+    w.write_all(&[status as u8])?;
+    write_payload(w, payload)?;
+    w.flush()?;
+    Ok(())
+}
+
+pub(crate) fn read_response<R: Read + ?Sized>(r: &mut R) -> anyhow::Result<Vec<u8>> {
+    let mut status = [0u8; 1];
+    r.read_exact(&mut status)?;
+    let status = AgentStatus::from_u8(status[0]);
+    let payload = read_payload(r)?;
+    if status == AgentStatus::Ok {
+        return Ok(payload);
+    }
+    let message = String::from_utf8_lossy(payload.as_slice()).to_string();
+    anyhow::Error::from(AgentError { status, message }).err()
+}
+
+pub(crate) fn verify_payload(data: &[u8], sign: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(4 + data.len() + sign.len());
+    payload.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    payload.extend_from_slice(data);
+    payload.extend_from_slice(sign);
+    payload
+}
+
+pub(crate) fn split_verify_payload(payload: &[u8]) -> anyhow::Result<(&[u8], &[u8])> {
+    if payload.len() < 4 {
+        return anyhow::anyhow!("agent verify payload too short").err();
+    }
+    let mut len = [0u8; 4];
+    len.copy_from_slice(&payload[..4]);
+    let len = u32::from_be_bytes(len) as usize;
+    if payload.len() - 4 < len {
+        return anyhow::anyhow!("agent verify payload too short").err();
+    }
+    Ok(payload[4..].split_at(len))
+}
@@ -0,0 +1,259 @@
+use crate::agent::protocol::{
+    read_request, split_verify_payload, write_response, AgentOp, AgentStatus, Request,
+};
+use crate::app::PassportEntity;
+use crate::PassportError;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufReader, BufWriter};
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+///默认的最大并发连接数，见 PassportAgent::set_max_connections
+pub const DEFAULT_MAX_CONNECTIONS: usize = 64;
+///默认的连接读写超时，见 PassportAgent::set_timeout
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+//accept 失败(如 EMFILE)后等待一段时间再重试，避免空转
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+///按 peer uid 授权的白名单，不在名单中的 uid 所有请求都会被拒绝
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgentPolicy {
+    rules: HashMap<u32, HashSet<AgentOp>>,
+}
+
+impl AgentPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    ///允许 uid 执行 ops，可多次调用累加
+    pub fn allow(mut self, uid: u32, ops: &[AgentOp]) -> Self {
+        self.rules
+            .entry(uid)
+            .or_default()
+            .extend(ops.iter().copied());
+        self
+    }
+    pub fn allow_all(self, uid: u32) -> Self {
+        self.allow(uid, &AgentOp::ALL)
+    }
+    ///允许当前进程的用户执行所有操作
+    pub fn allow_current_user(self) -> Self {
+        self.allow_all(current_uid())
+    }
+    //白名单中是否有其他用户，决定 socket 文件的权限
+    fn has_other_users(&self) -> bool {
+        let uid = current_uid();
+        self.rules.keys().any(|u| *u != uid)
+    }
+    ///uid 是否在白名单中，不在名单中的连接在 accept 后直接关闭
+    pub fn is_known(&self, uid: u32) -> bool {
+        self.rules.get(&uid).is_some_and(|ops| !ops.is_empty())
+    }
+    pub fn is_allowed(&self, uid: u32, op: AgentOp) -> bool {
+        self.rules
+            .get(&uid)
+            .map(|ops| ops.contains(&op))
+            .unwrap_or(false)
+    }
+}
+
+///持有 PassportEntity 的签名 agent，应用进程只需要能连接 unix socket，不再接触 key
+pub struct PassportAgent {
+    entity: PassportEntity,
+    policy: AgentPolicy,
+    max_connections: usize,
+    timeout: Option<Duration>,
+    connections: AtomicUsize,
+}
+
+impl PassportAgent {
+    pub fn new(entity: PassportEntity, policy: AgentPolicy) -> Self {
+        Self {
+            entity,
+            policy,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            timeout: Some(DEFAULT_TIMEOUT),
+            connections: AtomicUsize::new(0),
+        }
+    }
+
+    ///最大并发连接数，超出后新连接在 accept 后直接关闭
+    pub fn set_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    ///连接上读写的超时，空闲超过该时间的连接会被关闭并释放连接数，AgentClient 需要重新连接；None 为不超时
+    pub fn set_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    ///监听 path，已存在的 socket 文件会被删除；path 存在但不是 socket 时返回错误
+    ///白名单只有当前用户时 socket 权限为 0600，否则为 0666，由 peer uid 白名单控制访问
+    pub fn bind<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<UnixListener> {
+        let path = path.as_ref();
+        match std::fs::symlink_metadata(path) {
+            Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path)?,
+            Ok(_) => {
+                return Err(anyhow::anyhow!(
+                    "path[{}] exists and is not a socket",
+                    path.display()
+                ))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        //先在只有当前用户能进入的目录中创建 socket 并设置权限，再移动到 path，
+        //避免 socket 在设置权限之前以 umask 权限暴露
+        let name = match path.file_name() {
+            Some(s) => s.to_string_lossy().to_string(),
+            None => return Err(anyhow::anyhow!("path[{}] has no file name", path.display())),
+        };
+        let parent = match path.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p,
+            _ => Path::new("."),
+        };
+        let staging = parent.join(format!(".{}.{}.tmp", name, std::process::id()));
+        std::fs::DirBuilder::new().mode(0o700).create(&staging)?;
+        let staged = staging.join(name.as_str());
+        let res = UnixListener::bind(&staged).and_then(|listener| {
+            let mode = match self.policy.has_other_users() {
+                true => 0o666,
+                false => 0o600,
+            };
+            std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(mode))?;
+            std::fs::rename(&staged, path)?;
+            Ok(listener)
+        });
+        let _ = std::fs::remove_file(&staged);
+        std::fs::remove_dir(&staging)?;
+        Ok(res?)
+    }
+
+    ///每个连接一个线程，连接上可以连续发送多个请求
+    ///
+    ///accept 后立即检查 peer uid，不在白名单中的连接、超出最大并发数的连接直接关闭
+    pub fn serve(self: Arc<Self>, listener: UnixListener) -> anyhow::Result<()> {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("wd_passport agent: accept error:{}", e);
+                    std::thread::sleep(ACCEPT_BACKOFF);
+                    continue;
+                }
+            };
+            match peer_uid(&stream) {
+                Ok(uid) if self.policy.is_known(uid) => {}
+                _ => continue,
+            }
+            if self.connections.fetch_add(1, Ordering::SeqCst) >= self.max_connections {
+                self.connections.fetch_sub(1, Ordering::SeqCst);
+                continue;
+            }
+            if stream.set_read_timeout(self.timeout).is_err()
+                || stream.set_write_timeout(self.timeout).is_err()
+            {
+                self.connections.fetch_sub(1, Ordering::SeqCst);
+                continue;
+            }
+            let agent = self.clone();
+            std::thread::spawn(move || {
+                let _ = agent.handle_connection(stream);
+                agent.connections.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+        Ok(())
+    }
+
+    pub fn handle_connection(&self, stream: UnixStream) -> anyhow::Result<()> {
+        let uid = peer_uid(&stream)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = BufWriter::new(stream);
+        while let Some(req) = read_request(&mut reader)? {
+            let (status, payload) = self.handle(uid, &req);
+            write_response(&mut writer, status, payload.as_slice())?;
+        }
+        Ok(())
+    }
+
+    fn handle(&self, uid: u32, req: &Request) -> (AgentStatus, Vec<u8>) {
+        if !self.policy.is_allowed(uid, req.op) {
+            let message = format!("uid[{}] is not allowed to {}", uid, req.op.name());
+            return (AgentStatus::Denied, message.into_bytes());
+        }
+        let data = req.payload.as_slice();
+        let res = match req.op {
+            AgentOp::Sign => self.entity.sign_sha256(data, req.timestamp),
+            #[cfg(feature = "encrypt")]
+            AgentOp::Decrypt => self.entity.decrypt(data, req.timestamp),
+            #[cfg(feature = "encrypt")]
+            AgentOp::Encrypt => self.entity.encrypt(data, req.timestamp),
+            #[cfg(not(feature = "encrypt"))]
+            AgentOp::Decrypt | AgentOp::Encrypt => Err(anyhow::anyhow!(
+                "{} requires the encrypt feature",
+                req.op.name()
+            )),
+            AgentOp::Verify => split_verify_payload(data)
+                .and_then(|(data, sign)| self.entity.verify_sha256(data, sign, req.timestamp))
+                .map(|_| vec![]),
+        };
+        match res {
+            Ok(buf) => (AgentStatus::Ok, buf),
+            Err(e) => (error_status(&e), e.to_string().into_bytes()),
+        }
+    }
+}
+
+//按 PassportError 分类，其他错误为 AgentStatus::Error
+fn error_status(e: &anyhow::Error) -> AgentStatus {
+    match e.downcast_ref::<PassportError>() {
+        Some(PassportError::OutOfRange(_)) => AgentStatus::OutOfRange,
+        Some(PassportError::BadSignature) => AgentStatus::BadSignature,
+        _ => AgentStatus::Error,
+    }
+}
+
+fn current_uid() -> u32 {
+    unsafe { libc::getuid() }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn peer_uid(stream: &UnixStream) -> anyhow::Result<u32> {
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let res = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if res != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(cred.uid)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn peer_uid(stream: &UnixStream) -> anyhow::Result<u32> {
+    let mut uid: libc::uid_t = 0;
+    let mut gid: libc::gid_t = 0;
+    let res = unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) };
+    if res != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(uid)
+}
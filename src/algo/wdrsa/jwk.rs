@@ -0,0 +1,70 @@
+use crate::common::{Base64StdDecode, Base64StdEncode, PFErr, PFOk};
+use rsa::{BigUint, PublicKeyParts, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+
+///RFC 7517 RSA 公钥，n/e 为 base64 url safe 无填充的大端整数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(rename = "use", default, skip_serializing_if = "Option::is_none")]
+    pub use_: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alg: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+    pub n: String,
+    pub e: String,
+    ///扩展字段：公钥所属窗口的起始时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<i64>,
+}
+
+///RFC 7517 JWK Set
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+impl Jwk {
+    ///RS256 签名公钥
+    pub fn from_public_key(puk: &RsaPublicKey) -> anyhow::Result<Self> {
+        Self {
+            kty: "RSA".to_string(),
+            use_: Some("sig".to_string()),
+            alg: Some("RS256".to_string()),
+            kid: None,
+            n: puk.n().to_bytes_be().base64()?,
+            e: puk.e().to_bytes_be().base64()?,
+            window: None,
+        }
+        .ok()
+    }
+    pub fn set_kid<S: Into<String>>(mut self, kid: S) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+    pub fn set_window(mut self, window: i64) -> Self {
+        self.window = Some(window);
+        self
+    }
+    pub fn to_public_key(&self) -> anyhow::Result<RsaPublicKey> {
+        if self.kty != "RSA" {
+            return anyhow::anyhow!("unsupported jwk kty[{}]", self.kty).err();
+        }
+        let n = BigUint::from_bytes_be(self.n.as_str().try_decode_base64()?.as_slice());
+        let e = BigUint::from_bytes_be(self.e.as_str().try_decode_base64()?.as_slice());
+        let puk = RsaPublicKey::new(n, e)?;
+        Ok(puk)
+    }
+}
+
+impl Jwks {
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        let s = serde_json::to_string_pretty(self)?;
+        Ok(s)
+    }
+    pub fn from_json<S: AsRef<str>>(s: S) -> anyhow::Result<Self> {
+        let jwks = serde_json::from_str(s.as_ref())?;
+        Ok(jwks)
+    }
+}
@@ -1,23 +1,26 @@
 use crypto::digest::Digest;
 use crypto::sha1::Sha1;
 use rand::{CryptoRng, Error, RngCore};
-use std::iter::repeat;
+use std::fmt::{Debug, Formatter};
+use zeroize::Zeroizing;
 
 pub trait ConfuseRule: Send + Sync {
     fn confuse(&self, buf: &mut Vec<u8>);
 }
 
+//key 是由密钥推导出的随机数状态，drop 时清零
 pub struct RuleRand {
-    key: Vec<u8>,
+    key: Zeroizing<Vec<u8>>,
     confuse_rule: Box<dyn ConfuseRule>,
 }
 
 impl RuleRand {
-    fn raw_new(buf: &Vec<u8>, confuse_rule: Box<dyn ConfuseRule>) -> Self {
+    fn raw_new(buf: &[u8], confuse_rule: Box<dyn ConfuseRule>) -> Self {
         let mut hasher = Sha1::new();
-        hasher.input(buf.as_slice());
-        let mut key: Vec<u8> = repeat(0).take((hasher.output_bits() + 7) / 8).collect();
+        hasher.input(buf);
+        let mut key = Zeroizing::new(vec![0; hasher.output_bits().div_ceil(8)]);
         hasher.result(key.as_mut_slice());
+        hasher.reset();
         Self { key, confuse_rule }
     }
     pub fn new<Key>(key: Key) -> Self
@@ -26,8 +29,9 @@ impl RuleRand {
     {
         RuleRandBuilder::new(key).build()
     }
-    pub fn key(&self) -> Vec<u8> {
-        self.key.clone()
+    #[allow(dead_code)]
+    pub(crate) fn key(&self) -> &[u8] {
+        self.key.as_slice()
     }
     fn confuse(&mut self) {
         self.confuse_rule.confuse(&mut self.key);
@@ -76,15 +80,23 @@ impl RngCore for RuleRand {
     }
 }
 
+impl Debug for RuleRand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RuleRand")
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
 pub struct RuleRandBuilder {
-    key: Vec<u8>,
+    key: Zeroizing<Vec<u8>>,
     confuse_rule: Box<dyn ConfuseRule>,
 }
 
 impl Default for RuleRandBuilder {
     fn default() -> Self {
-        let mut rng = rand::thread_rng();
-        let mut key = Vec::with_capacity(1024);
+        let mut rng = rand::rngs::OsRng;
+        let mut key = Zeroizing::new(vec![0; 1024]);
         rng.fill_bytes(key.as_mut_slice());
         let confuse_rule = Box::new(ConfuseRuleDefault);
         Self { key, confuse_rule }
@@ -96,33 +108,49 @@ impl RuleRandBuilder {
     where
         Vec<u8>: From<Key>,
     {
-        let key = Vec::from(key);
+        let key = Zeroizing::new(Vec::from(key));
         let confuse_rule = Box::new(ConfuseRuleDefault);
         Self { key, confuse_rule }
     }
     #[allow(dead_code)]
     pub fn reset(mut self, buf: Vec<u8>) -> Self {
-        self.key = buf;
+        //旧的 key 随 Zeroizing drop 清零
+        self.key = Zeroizing::new(buf);
         self
     }
     #[allow(dead_code)]
-    pub fn confuse_key(mut self, mut buf: Vec<u8>) -> Self {
-        self.key.append(&mut buf);
+    pub fn confuse_key(mut self, buf: Vec<u8>) -> Self {
+        let buf = Zeroizing::new(buf);
+        self.extend_key(buf.as_slice());
         self
     }
     #[allow(dead_code)]
     pub fn confuse_key_i64(mut self, i: i64) -> Self {
-        let mut buf = i.to_be_bytes().to_vec();
-        self.key.append(&mut buf);
+        self.extend_key(&i.to_be_bytes());
         self
     }
+    //Vec 扩容时旧的内存不会被清零，所以一次性分配好再整体替换
+    fn extend_key(&mut self, buf: &[u8]) {
+        let mut key = Zeroizing::new(Vec::with_capacity(self.key.len() + buf.len()));
+        key.extend_from_slice(self.key.as_slice());
+        key.extend_from_slice(buf);
+        self.key = key;
+    }
     #[allow(dead_code)]
     pub fn confuse_rule(mut self, rule: Box<dyn ConfuseRule>) -> Self {
         self.confuse_rule = rule;
         self
     }
     pub fn build(self) -> RuleRand {
-        RuleRand::raw_new(self.key.as_ref(), self.confuse_rule)
+        RuleRand::raw_new(self.key.as_slice(), self.confuse_rule)
+    }
+}
+
+impl Debug for RuleRandBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RuleRandBuilder")
+            .field("key", &"<redacted>")
+            .finish()
     }
 }
 
@@ -130,7 +158,11 @@ pub struct ConfuseRuleDefault;
 
 impl ConfuseRule for ConfuseRuleDefault {
     fn confuse(&self, buf: &mut Vec<u8>) {
-        let mut list = vec![];
+        if buf.is_empty() {
+            return;
+        }
+        //一次分配到位，push 时不会重新分配而在堆上留下未清零的副本
+        let mut list = Zeroizing::new(Vec::with_capacity(buf.len()));
         let len = buf.len() - 1;
         for (i, item) in buf.iter().enumerate() {
             let number = if i < len {
@@ -163,6 +195,32 @@ mod test {
         println!("{:?}", buf)
     }
 
+    #[test]
+    fn test_rule_rand_empty() {
+        let mut buf = vec![];
+        ConfuseRuleDefault.confuse(&mut buf);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_rule_rand_builder_default() {
+        let a = RuleRandBuilder::default().build().key().to_vec();
+        let b = RuleRandBuilder::default().build().key().to_vec();
+        assert_eq!(a.len(), 20);
+        assert_ne!(a, b, "默认随机种子不应相同");
+    }
+
+    #[test]
+    fn test_rule_rand_debug_redacted() {
+        let builder = RuleRandBuilder::new("hello world").confuse_key_i64(123456);
+        let s = format!("{:?}", builder);
+        assert!(s.contains("<redacted>"));
+        assert!(!s.contains("104, 101, 108"), "debug 输出包含了密钥");
+        let rng = builder.build();
+        let s = format!("{:?}", rng);
+        assert!(s.contains("<redacted>"));
+    }
+
     #[test]
     fn test_rule_rand_generate() {
         let mut rng = RuleRandBuilder::new("hello world")
@@ -2,10 +2,12 @@
 
 use crate::algo::wdrsa::{RsaEntity, RuleRandBuilder};
 use rsa::Pkcs1v15Encrypt;
+use std::fmt::{Debug, Formatter};
+use zeroize::Zeroizing;
 
 pub struct RsaEntityBuilder {
     bit_size: usize,
-    key: Vec<u8>,
+    key: Zeroizing<Vec<u8>>,
     key_confuse_number: i64,
     // rng:RuleRand,
 }
@@ -16,7 +18,7 @@ impl RsaEntityBuilder {
         Key: AsRef<[u8]>,
     {
         let key_confuse_number = rand::random();
-        let key = Vec::from(key.as_ref());
+        let key = Zeroizing::new(Vec::from(key.as_ref()));
         let bit_size = 2048;
         Self {
             bit_size,
@@ -32,10 +34,21 @@ impl RsaEntityBuilder {
         self.key_confuse_number = nb;
         self
     }
-    pub fn build_pkcs15(self) -> anyhow::Result<RsaEntity<Pkcs1v15Encrypt>> {
-        let mut rand = RuleRandBuilder::new(self.key)
+    pub fn build_pkcs15(mut self) -> anyhow::Result<RsaEntity<Pkcs1v15Encrypt>> {
+        let key = std::mem::take(&mut *self.key);
+        let mut rand = RuleRandBuilder::new(key)
             .confuse_key_i64(self.key_confuse_number)
             .build();
         RsaEntity::new(&mut rand, self.bit_size, Pkcs1v15Encrypt)
     }
 }
+
+impl Debug for RsaEntityBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RsaEntityBuilder")
+            .field("bit_size", &self.bit_size)
+            .field("key", &"<redacted>")
+            .field("key_confuse_number", &self.key_confuse_number)
+            .finish()
+    }
+}
@@ -0,0 +1,382 @@
+use crate::algo::wdrsa::RsaEntity;
+use crate::common::{PFErr, PFOk};
+use rsa::pkcs8::der::asn1::{
+    AnyRef, BitStringRef, GeneralizedTime, ObjectIdentifier, OctetStringRef, UIntRef, UtcTime,
+};
+use rsa::pkcs8::der::pem::PemLabel;
+use rsa::pkcs8::der::{Decode, Encode};
+use rsa::pkcs8::spki::{AlgorithmIdentifier, SubjectPublicKeyInfo};
+use rsa::pkcs8::{DecodePublicKey, EncodePublicKey};
+use rsa::sha2::{Digest, Sha256};
+use rsa::{PaddingScheme, Pkcs1v15Sign, PublicKey, RsaPublicKey};
+use std::time::Duration;
+use x509_cert::certificate::{Certificate, TbsCertificate, Version};
+use x509_cert::ext::pkix::{
+    AuthorityKeyIdentifier, BasicConstraints, KeyUsage, KeyUsages, SubjectKeyIdentifier,
+};
+use x509_cert::ext::Extension;
+use x509_cert::name::{Name, RdnSequence};
+use x509_cert::time::{Time, Validity};
+
+//sha256WithRSAEncryption
+const SHA256_WITH_RSA_ENCRYPTION: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.11");
+const ID_CE_SUBJECT_KEY_IDENTIFIER: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.14");
+const ID_CE_KEY_USAGE: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.15");
+const ID_CE_BASIC_CONSTRAINTS: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.19");
+const ID_CE_AUTHORITY_KEY_IDENTIFIER: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.35");
+
+///X.509 证书参数，subject/issuer 使用 RFC 4514 字符串，例如 "CN=wd_passport,O=example"
+///其中的 {window} 会被替换为窗口起始时间
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateParams {
+    pub subject: String,
+    ///为 None 时与 subject 相同(自签名)；与 subject 不同时证书仍由窗口私钥签名，只有名称不同
+    pub issuer: Option<String>,
+}
+
+impl Default for CertificateParams {
+    fn default() -> Self {
+        Self {
+            subject: "CN=wd_passport {window}".to_string(),
+            issuer: None,
+        }
+    }
+}
+
+impl CertificateParams {
+    pub fn new<S: Into<String>>(subject: S) -> Self {
+        Self {
+            subject: subject.into(),
+            issuer: None,
+        }
+    }
+    pub fn set_issuer<S: Into<String>>(mut self, issuer: S) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+    pub(crate) fn subject_for(&self, window: i64) -> String {
+        self.subject
+            .replace("{window}", window.to_string().as_str())
+    }
+    pub(crate) fn issuer_for(&self, window: i64) -> String {
+        match self.issuer.as_ref() {
+            Some(s) => s.replace("{window}", window.to_string().as_str()),
+            None => self.subject_for(window),
+        }
+    }
+}
+
+//签发证书的参数，ca 为 true 时签发根证书，否则签发仅限签名的窗口证书
+pub(crate) struct IssueRequest<'a> {
+    pub issuer: &'a str,
+    pub subject: &'a str,
+    pub subject_key: &'a RsaPublicKey,
+    pub not_before_sec: i64,
+    pub not_after_sec: i64,
+    pub ca: bool,
+    pub authority_key_id: Option<&'a [u8]>,
+}
+
+//公钥指纹：SubjectPublicKeyInfo DER 的 sha256
+pub(crate) fn spki_fingerprint(puk: &RsaPublicKey) -> anyhow::Result<Vec<u8>> {
+    let spki_der = puk.to_public_key_der()?;
+    Ok(Sha256::digest(spki_der.as_bytes()).to_vec())
+}
+
+//公钥标识：公钥指纹的前 20 字节
+pub(crate) fn key_identifier(puk: &RsaPublicKey) -> anyhow::Result<Vec<u8>> {
+    let mut id = spki_fingerprint(puk)?;
+    id.truncate(20);
+    Ok(id)
+}
+
+//签发一张 v3 证书：signer 用自己的私钥为 subject_key 签名
+pub(crate) fn issue_certificate<P: PaddingScheme + Copy>(
+    signer: &RsaEntity<P>,
+    req: IssueRequest,
+) -> anyhow::Result<Vec<u8>> {
+    let spki_der = req.subject_key.to_public_key_der()?;
+    let spki = SubjectPublicKeyInfo::from_der(spki_der.as_bytes())?;
+    let fingerprint = Sha256::digest(spki_der.as_bytes());
+
+    //序列号取公钥指纹前 16 字节，保证为正数且没有前导 0
+    let mut serial = fingerprint[..16].to_vec();
+    serial[0] = (serial[0] & 0x7f) | 0x40;
+
+    let issuer = RdnSequence::encode_from_string(req.issuer)?;
+    let subject = RdnSequence::encode_from_string(req.subject)?;
+
+    let (usage, path_len) = if req.ca {
+        (KeyUsages::KeyCertSign | KeyUsages::CRLSign, Some(0))
+    } else {
+        (
+            KeyUsages::DigitalSignature | KeyUsages::NonRepudiation,
+            None,
+        )
+    };
+    let key_usage = KeyUsage(usage).to_vec()?;
+    let basic_constraints = BasicConstraints {
+        ca: req.ca,
+        path_len_constraint: path_len,
+    }
+    .to_vec()?;
+    let ski = SubjectKeyIdentifier(OctetStringRef::new(&fingerprint[..20])?).to_vec()?;
+    let aki = match req.authority_key_id {
+        Some(id) => Some(
+            AuthorityKeyIdentifier {
+                key_identifier: Some(OctetStringRef::new(id)?),
+                authority_cert_issuer: None,
+                authority_cert_serial_number: None,
+            }
+            .to_vec()?,
+        ),
+        None => None,
+    };
+
+    let mut extensions = vec![
+        Extension {
+            extn_id: ID_CE_BASIC_CONSTRAINTS,
+            critical: true,
+            extn_value: basic_constraints.as_slice(),
+        },
+        Extension {
+            extn_id: ID_CE_KEY_USAGE,
+            critical: true,
+            extn_value: key_usage.as_slice(),
+        },
+        Extension {
+            extn_id: ID_CE_SUBJECT_KEY_IDENTIFIER,
+            critical: false,
+            extn_value: ski.as_slice(),
+        },
+    ];
+    if let Some(aki) = aki.as_ref() {
+        extensions.push(Extension {
+            extn_id: ID_CE_AUTHORITY_KEY_IDENTIFIER,
+            critical: false,
+            extn_value: aki.as_slice(),
+        });
+    }
+
+    let algorithm = AlgorithmIdentifier {
+        oid: SHA256_WITH_RSA_ENCRYPTION,
+        parameters: Some(AnyRef::NULL),
+    };
+    let tbs = TbsCertificate {
+        version: Version::V3,
+        serial_number: UIntRef::new(serial.as_slice())?,
+        signature: algorithm,
+        issuer: Name::from_der(issuer.as_slice())?,
+        validity: Validity {
+            not_before: x509_time(req.not_before_sec)?,
+            not_after: x509_time(req.not_after_sec)?,
+        },
+        subject: Name::from_der(subject.as_slice())?,
+        subject_public_key_info: spki,
+        issuer_unique_id: None,
+        subject_unique_id: None,
+        extensions: Some(extensions),
+    };
+    let tbs_der = tbs.to_vec()?;
+    let signature = signer.sign_sha256(tbs_der.as_slice())?;
+    let cert = Certificate {
+        tbs_certificate: tbs,
+        signature_algorithm: algorithm,
+        signature: BitStringRef::from_bytes(signature.as_slice())?,
+    };
+    Ok(cert.to_vec()?)
+}
+
+///根证书认可的一个窗口
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndorsedWindow {
+    ///窗口起始时间(证书 notBefore)
+    pub window: i64,
+    ///窗口结束时间(证书 notAfter)
+    pub not_after: i64,
+    pub public_key: RsaPublicKey,
+}
+
+///验证方只需要固定根公钥(或根证书)，即可校验任意窗口证书
+#[derive(Debug, Clone)]
+pub struct RootVerifier {
+    root: RsaPublicKey,
+    subject: String,
+    interval: i64,
+    //根证书的有效期，只固定根公钥时为 None
+    validity: Option<(i64, i64)>,
+}
+
+impl RootVerifier {
+    ///固定根公钥与根名称，窗口证书的 issuer 必须为 subject；没有根证书，不限制窗口证书的有效期
+    pub fn new(root: RsaPublicKey, subject: &str, upgrade_cycle: Duration) -> anyhow::Result<Self> {
+        let interval = upgrade_cycle.as_secs() as i64;
+        if interval <= 0 {
+            return anyhow::anyhow!("upgrade cycle must be at least one second").err();
+        }
+        Self {
+            root,
+            subject: subject.to_string(),
+            interval,
+            validity: None,
+        }
+        .ok()
+    }
+    ///固定根证书：校验其自签名，窗口证书的 issuer 必须与根证书 subject 一致，有效期必须在根证书之内
+    pub fn from_certificate_der(der: &[u8], upgrade_cycle: Duration) -> anyhow::Result<Self> {
+        let cert = Certificate::from_der(der)?;
+        let tbs = &cert.tbs_certificate;
+        let spki = tbs.subject_public_key_info.to_vec()?;
+        let root = RsaPublicKey::from_public_key_der(spki.as_slice())?;
+        let (ca, _) = basic_constraints(&cert)?;
+        if !ca {
+            return anyhow::anyhow!("root certificate is not a CA").err();
+        }
+        if !key_usage(&cert)?.0.contains(KeyUsages::KeyCertSign) {
+            return anyhow::anyhow!("root certificate can not sign certificates").err();
+        }
+        verify_signature(&root, &cert)?;
+        let mut verifier = Self::new(root, tbs.subject.to_string().as_str(), upgrade_cycle)?;
+        verifier.validity = Some(validity(&cert));
+        verifier.ok()
+    }
+    pub fn from_certificate_pem(pem: &str, upgrade_cycle: Duration) -> anyhow::Result<Self> {
+        let (label, der) = rsa::pkcs8::der::pem::decode_vec(pem.as_bytes())
+            .map_err(|e| anyhow::anyhow!("certificate pem decode error:{}", e))?;
+        if label != Certificate::PEM_LABEL {
+            return anyhow::anyhow!("unexpected pem label[{}]", label).err();
+        }
+        Self::from_certificate_der(der.as_slice(), upgrade_cycle)
+    }
+    pub fn root_key(&self) -> &RsaPublicKey {
+        &self.root
+    }
+    ///校验窗口证书由根私钥签发，返回窗口信息；根从未签发过的窗口会被拒绝
+    ///
+    ///窗口证书必须：issuer 为根证书 subject，非 CA，只用于签名(digitalSignature/nonRepudiation)，
+    ///有效期恰好是一个对齐的窗口，并且在根证书的有效期之内
+    pub fn verify_window_certificate_der(&self, der: &[u8]) -> anyhow::Result<EndorsedWindow> {
+        let cert = Certificate::from_der(der)?;
+        verify_signature(&self.root, &cert)?;
+        let tbs = &cert.tbs_certificate;
+        let issuer = tbs.issuer.to_string();
+        if issuer != self.subject {
+            return anyhow::anyhow!(
+                "issuer[{}] is not the pinned root[{}]",
+                issuer,
+                self.subject
+            )
+            .err();
+        }
+        let (ca, critical) = basic_constraints(&cert)?;
+        if ca || !critical {
+            return anyhow::anyhow!("window certificate must be a critical non-CA certificate")
+                .err();
+        }
+        let usage = key_usage(&cert)?.0;
+        if !usage.contains(KeyUsages::DigitalSignature)
+            || usage
+                .into_iter()
+                .any(|u| !matches!(u, KeyUsages::DigitalSignature | KeyUsages::NonRepudiation))
+        {
+            return anyhow::anyhow!("window certificate key usage must be digital signature only")
+                .err();
+        }
+        let (window, not_after) = validity(&cert);
+        if window % self.interval != 0 || not_after != window + self.interval - 1 {
+            return anyhow::anyhow!(
+                "validity[{}, {}] is not a window of interval[{}]",
+                window,
+                not_after,
+                self.interval
+            )
+            .err();
+        }
+        if let Some((root_not_before, root_not_after)) = self.validity {
+            if window < root_not_before || not_after > root_not_after {
+                return anyhow::anyhow!(
+                    "window[{}] is outside the root validity[{}, {}]",
+                    window,
+                    root_not_before,
+                    root_not_after
+                )
+                .err();
+            }
+        }
+        let spki = tbs.subject_public_key_info.to_vec()?;
+        let public_key = RsaPublicKey::from_public_key_der(spki.as_slice())?;
+        EndorsedWindow {
+            window,
+            not_after,
+            public_key,
+        }
+        .ok()
+    }
+    pub fn verify_window_certificate_pem(&self, pem: &str) -> anyhow::Result<EndorsedWindow> {
+        let (label, der) = rsa::pkcs8::der::pem::decode_vec(pem.as_bytes())
+            .map_err(|e| anyhow::anyhow!("certificate pem decode error:{}", e))?;
+        if label != Certificate::PEM_LABEL {
+            return anyhow::anyhow!("unexpected pem label[{}]", label).err();
+        }
+        self.verify_window_certificate_der(der.as_slice())
+    }
+}
+
+fn verify_signature(puk: &RsaPublicKey, cert: &Certificate) -> anyhow::Result<()> {
+    if cert.signature_algorithm.oid != SHA256_WITH_RSA_ENCRYPTION {
+        return anyhow::anyhow!(
+            "unsupported signature algorithm[{}]",
+            cert.signature_algorithm.oid
+        )
+        .err();
+    }
+    let tbs = cert.tbs_certificate.to_vec()?;
+    let hashed = Sha256::digest(tbs.as_slice());
+    let sign = match cert.signature.as_bytes() {
+        Some(s) => s,
+        None => return anyhow::anyhow!("certificate signature has unused bits").err(),
+    };
+    puk.verify(Pkcs1v15Sign::new::<Sha256>(), hashed.as_slice(), sign)?;
+    Ok(())
+}
+
+//返回 (是否 CA, 扩展是否为 critical)，没有该扩展时视为非 CA
+fn basic_constraints(cert: &Certificate) -> anyhow::Result<(bool, bool)> {
+    match cert.tbs_certificate.get::<BasicConstraints>()? {
+        Some((critical, bc)) => Ok((bc.ca, critical)),
+        None => Ok((false, false)),
+    }
+}
+
+//必须有 critical 的 KeyUsage 扩展
+fn key_usage(cert: &Certificate) -> anyhow::Result<KeyUsage> {
+    match cert.tbs_certificate.get::<KeyUsage>()? {
+        Some((true, usage)) => Ok(usage),
+        Some((false, _)) => anyhow::anyhow!("certificate key usage is not critical").err(),
+        None => anyhow::anyhow!("certificate has no key usage").err(),
+    }
+}
+
+//证书的 (notBefore, notAfter)，unix 时间戳
+fn validity(cert: &Certificate) -> (i64, i64) {
+    let validity = &cert.tbs_certificate.validity;
+    (
+        validity.not_before.to_unix_duration().as_secs() as i64,
+        validity.not_after.to_unix_duration().as_secs() as i64,
+    )
+}
+
+//RFC 5280：2049 年及以前使用 UTCTime，之后使用 GeneralizedTime
+fn x509_time(timestamp_sec: i64) -> anyhow::Result<Time> {
+    if timestamp_sec < 0 {
+        return anyhow::anyhow!("timestamp[{}] before 1970 is not supported", timestamp_sec).err();
+    }
+    let duration = Duration::from_secs(timestamp_sec as u64);
+    //2050-01-01 00:00:00 UTC
+    if timestamp_sec < 2524608000 {
+        Ok(UtcTime::from_unix_duration(duration)?.into())
+    } else {
+        Ok(GeneralizedTime::from_unix_duration(duration)?.into())
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(feature = "sign")]
+use crate::common::PFErr;
+#[cfg(feature = "sign")]
+use crate::PassportError;
+
+///绑定时间戳签名的域分隔标记，v1
+pub const BOUND_SIGN_TAG: &[u8] = b"wd_passport.bound.v1\0";
+
+///绑定时间戳签名实际签名的字节：
+///
+///`BOUND_SIGN_TAG || timestamp_sec(i64 大端) || len(context)(u32 大端) || context || data`
+///
+///context 为 None 与空字符串相同。data 在最后，context 带长度前缀，因此编码没有歧义；
+///context 超过 u32::MAX 字节时返回错误
+pub fn bound_message(
+    data: &[u8],
+    timestamp_sec: i64,
+    context: Option<&str>,
+) -> anyhow::Result<Vec<u8>> {
+    let context = context.unwrap_or_default().as_bytes();
+    let context_len = u32::try_from(context.len())?;
+    let mut message = Vec::with_capacity(BOUND_SIGN_TAG.len() + 12 + context.len() + data.len());
+    message.extend_from_slice(BOUND_SIGN_TAG);
+    message.extend_from_slice(&timestamp_sec.to_be_bytes());
+    message.extend_from_slice(&context_len.to_be_bytes());
+    message.extend_from_slice(context);
+    message.extend_from_slice(data);
+    Ok(message)
+}
+
+//以 BOUND_SIGN_TAG 开头的数据只能由 sign_sha256_bound 签名，否则普通签名可以伪造绑定签名
+#[cfg(feature = "sign")]
+pub(crate) fn check_unbound(data: &[u8]) -> anyhow::Result<()> {
+    if data.starts_with(BOUND_SIGN_TAG) {
+        return anyhow::Error::from(PassportError::BoundTag).err();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::bound_message;
+
+    fn bound(data: &[u8], timestamp_sec: i64, context: Option<&str>) -> Vec<u8> {
+        bound_message(data, timestamp_sec, context).expect("编码失败")
+    }
+
+    #[test]
+    fn test_bound_message() {
+        let message = bound(b"hi", 1679919556, Some("login"));
+        let mut expect = b"wd_passport.bound.v1\0".to_vec();
+        expect.extend_from_slice(&[0, 0, 0, 0, 0x64, 0x21, 0x89, 0xc4]);
+        expect.extend_from_slice(&[0, 0, 0, 5]);
+        expect.extend_from_slice(b"loginhi");
+        assert_eq!(message, expect);
+        assert_eq!(
+            bound(b"hi", 1679919556, None),
+            bound(b"hi", 1679919556, Some(""))
+        );
+        //context 与 data 的边界不能移动
+        assert_ne!(
+            bound(b"inhi", 1679919556, Some("log")),
+            bound(b"hi", 1679919556, Some("login"))
+        );
+        assert_ne!(
+            bound(b"hi", 1679919556, None),
+            bound(b"hi", 1679919557, None)
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+///时间来源，返回 unix 时间戳(秒)，供 sign_now/verify_now/encrypt_now 选择窗口
+pub trait Clock: Send + Sync {
+    fn now(&self) -> i64;
+}
+
+///系统时间，PassportEntity 的默认时钟
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
+        }
+    }
+}
+
+///固定时间，可以手动调整，用于测试跨窗口轮换
+///
+///放在 Arc 中交给 PassportEntity 后，仍然可以通过保留的 Arc 调整时间
+#[derive(Debug, Default)]
+pub struct FixedClock {
+    now: AtomicI64,
+}
+
+impl FixedClock {
+    pub fn new(timestamp_sec: i64) -> Self {
+        Self {
+            now: AtomicI64::new(timestamp_sec),
+        }
+    }
+
+    pub fn set(&self, timestamp_sec: i64) {
+        self.now.store(timestamp_sec, Ordering::SeqCst);
+    }
+
+    ///时间前进 sec 秒，sec 为负数时后退
+    pub fn advance(&self, sec: i64) {
+        self.now.fetch_add(sec, Ordering::SeqCst);
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> i64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+///在另一个时钟上加固定偏移，用于校正本机时间误差
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OffsetClock<C> {
+    inner: C,
+    offset_sec: i64,
+}
+
+impl<C: Clock> OffsetClock<C> {
+    pub fn new(inner: C, offset_sec: i64) -> Self {
+        Self { inner, offset_sec }
+    }
+
+    pub fn offset_sec(&self) -> i64 {
+        self.offset_sec
+    }
+}
+
+impl<C: Clock> Clock for OffsetClock<C> {
+    fn now(&self) -> i64 {
+        self.inner.now() + self.offset_sec
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> i64 {
+        (**self).now()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Clock, FixedClock, OffsetClock, SystemClock};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_clock() {
+        let fixed = Arc::new(FixedClock::new(1679919556));
+        let offset = OffsetClock::new(fixed.clone(), -60);
+        assert_eq!(fixed.now(), 1679919556);
+        assert_eq!(offset.now(), 1679919496);
+        fixed.advance(60 * 60 * 24 * 30);
+        assert_eq!(offset.now(), 1679919496 + 60 * 60 * 24 * 30);
+        fixed.set(1);
+        assert_eq!(offset.now(), -59);
+        //系统时间晚于 2023 年
+        assert!(SystemClock.now() > 1675641600);
+    }
+}
@@ -1,14 +1,87 @@
-use crate::algo::{RsaEntity, RsaEntityBuilder};
+use crate::algo::{
+    certificate_der_to_pem, issue_certificate, key_identifier, CertificateParams, IssueRequest,
+    Jwks, PrivateKeyFormat, PublicKeyFormat, RootVerifier, RsaEntity, RsaEntityBuilder,
+};
+use crate::app::bound::check_unbound;
+use crate::app::{bound_message, Clock, SystemClock};
 use crate::common::*;
+use crate::{window_start, PassportError};
 use chrono::{DateTime, TimeZone};
+use rand::rngs::OsRng;
 use rsa::pkcs1::EncodeRsaPublicKey;
 use rsa::Pkcs1v15Encrypt;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
 use std::time::Duration;
+use zeroize::Zeroizing;
+
+///窗口证书的生成方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyMode {
+    ///由 key 和窗口起始时间确定性推导，相同参数在任何地方都能得到相同的证书
+    Derived,
+    ///由系统安全随机数生成，无法再次推导，需要调用方自行持久化
+    Random,
+    ///由调用方导入的已有证书(私钥或仅公钥)
+    Imported,
+}
+
+///PassportEntity 的内存占用统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryUsage {
+    ///窗口数量
+    pub windows: usize,
+    ///内存中私钥的份数，每个窗口最多一份，仅导入公钥的窗口没有私钥
+    pub private_keys: usize,
+    ///公私钥大数占用的堆内存(近似值)
+    pub key_bytes: usize,
+    ///包含结构体与索引表在内的总占用(近似值)
+    pub total_bytes: usize,
+}
+
+///窗口公钥的标识，fingerprint 为 SubjectPublicKeyInfo DER 的 sha256 hex，
+///key_id 为其前 20 字节，与窗口证书的 subject key identifier 相同
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyFingerprint {
+    pub window: i64,
+    pub fingerprint: String,
+    pub key_id: String,
+}
+
+//由根私钥派生时追加在 key 之后，与窗口证书的派生区分开
+const ROOT_KEY_DOMAIN: &[u8] = b"wd_passport.root";
+
+//长期有效的根证书，为每个窗口公钥签发证书
+struct RootKey {
+    entity: RsaEntity<Pkcs1v15Encrypt>,
+    subject: String,
+}
 
 pub struct PassportEntity {
     certs: HashMap<i64, RsaEntity<Pkcs1v15Encrypt>>,
+    //窗口公钥的指纹与 key id，放入窗口时计算一次
+    fingerprints: HashMap<i64, KeyFingerprint>,
+    //指纹与 key id 到窗口起始时间，多个窗口使用同一公钥时为最早的窗口
+    by_fingerprint: HashMap<String, i64>,
     interval: i64,
+    mode: KeyMode,
+    imported: HashSet<i64>,
+    root: Option<RootKey>,
+    clock: Box<dyn Clock>,
+}
+
+impl Debug for PassportEntity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut windows: Vec<&i64> = self.certs.keys().collect();
+        windows.sort();
+        f.debug_struct("PassportEntity")
+            .field("interval", &self.interval)
+            .field("mode", &self.mode)
+            .field("windows", &windows)
+            .field("imported", &self.imported)
+            .field("root", &self.root.as_ref().map(|r| r.subject.as_str()))
+            .finish()
+    }
 }
 
 impl PassportEntity {
@@ -22,28 +95,210 @@ impl PassportEntity {
         let interval = upgrade_cycle.as_secs() as i64;
         let list =
             PassportEntity::generate_rsa_entity(key, work_range_utc_sec, interval, bit_size)?;
-        let mut certs = HashMap::new();
+        Self::from_list(list, interval, KeyMode::Derived)
+    }
+
+    ///每个窗口的证书都由系统安全随机数(OsRng)生成，不依赖共享密钥
+    pub fn new_random<TZ: TimeZone>(
+        work_range_utc_sec: (DateTime<TZ>, DateTime<TZ>),
+        upgrade_cycle: Duration,
+        bit_size: usize,
+    ) -> anyhow::Result<Self> {
+        let interval = upgrade_cycle.as_secs() as i64;
+        let list =
+            PassportEntity::generate_random_rsa_entity(work_range_utc_sec, interval, bit_size)?;
+        Self::from_list(list, interval, KeyMode::Random)
+    }
+
+    fn from_list(
+        list: Vec<(i64, RsaEntity<Pkcs1v15Encrypt>)>,
+        interval: i64,
+        mode: KeyMode,
+    ) -> anyhow::Result<Self> {
+        let mut entity = Self {
+            certs: HashMap::new(),
+            fingerprints: HashMap::new(),
+            by_fingerprint: HashMap::new(),
+            interval,
+            mode,
+            imported: HashSet::new(),
+            root: None,
+            clock: Box::new(SystemClock),
+        };
         for (index, et) in list.into_iter() {
-            certs.insert(index, et);
+            entity.insert_window(index, et)?;
         }
-        Self { certs, interval }.ok()
+        entity.ok()
     }
 
-    pub(crate) fn generate_rsa_entity<Key: AsRef<[u8]>, TZ: TimeZone>(
-        key: Key,
+    //放入或替换一个窗口，同时更新指纹索引
+    fn insert_window(
+        &mut self,
+        window: i64,
+        entity: RsaEntity<Pkcs1v15Encrypt>,
+    ) -> anyhow::Result<()> {
+        let fp = KeyFingerprint {
+            window,
+            fingerprint: entity.fingerprint()?,
+            key_id: entity.key_id()?,
+        };
+        if let Some(old) = self.fingerprints.remove(&window) {
+            for key in [old.fingerprint, old.key_id] {
+                if self.by_fingerprint.get(&key) != Some(&window) {
+                    continue;
+                }
+                //被替换的公钥可能还被其他窗口使用
+                let other = self
+                    .fingerprints
+                    .values()
+                    .filter(|f| f.fingerprint == key || f.key_id == key)
+                    .map(|f| f.window)
+                    .min();
+                match other {
+                    Some(w) => self.by_fingerprint.insert(key, w),
+                    None => self.by_fingerprint.remove(&key),
+                };
+            }
+        }
+        for key in [fp.fingerprint.clone(), fp.key_id.clone()] {
+            self.by_fingerprint
+                .entry(key)
+                .and_modify(|w| *w = (*w).min(window))
+                .or_insert(window);
+        }
+        self.fingerprints.insert(window, fp);
+        self.certs.insert(window, entity);
+        Ok(())
+    }
+
+    ///不包含任何窗口，窗口全部通过 import_* 加入
+    pub fn empty(upgrade_cycle: Duration) -> anyhow::Result<Self> {
+        let interval = upgrade_cycle.as_secs() as i64;
+        if interval <= 0 {
+            return anyhow::anyhow!("upgrade cycle must be at least one second").err();
+        }
+        Self::from_list(vec![], interval, KeyMode::Imported)
+    }
+
+    ///替换 sign_now/verify_now/encrypt_now 使用的时钟，默认为 SystemClock
+    pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.set_clock(clock);
+        self
+    }
+
+    pub fn set_clock<C: Clock + 'static>(&mut self, clock: C) {
+        self.clock = Box::new(clock);
+    }
+
+    ///当前时钟的 unix 时间戳(秒)
+    pub fn now(&self) -> i64 {
+        self.clock.now()
+    }
+
+    pub fn mode(&self) -> KeyMode {
+        self.mode
+    }
+
+    pub fn upgrade_cycle(&self) -> Duration {
+        Duration::from_secs(self.interval as u64)
+    }
+
+    ///timestamp_sec 所在窗口的证书来源，窗口不存在时返回 None
+    pub fn window_source(&self, timestamp_sec: i64) -> Option<KeyMode> {
+        let window = self.window_of(timestamp_sec);
+        if !self.certs.contains_key(&window) {
+            return None;
+        }
+        if self.imported.contains(&window) {
+            return Some(KeyMode::Imported);
+        }
+        Some(self.mode)
+    }
+
+    ///把已有证书放入 timestamp_sec 所在的窗口，已存在的窗口会被替换，返回窗口起始时间
+    pub fn import_window(
+        &mut self,
+        timestamp_sec: i64,
+        entity: RsaEntity<Pkcs1v15Encrypt>,
+    ) -> anyhow::Result<i64> {
+        let window = self.window_of(timestamp_sec);
+        self.insert_window(window, entity)?;
+        self.imported.insert(window);
+        Ok(window)
+    }
+    ///导入 pkcs1 或 pkcs8 pem 私钥
+    pub fn import_private_pem(&mut self, timestamp_sec: i64, pem: &str) -> anyhow::Result<i64> {
+        let entity = RsaEntity::from_private_pem(pem, Pkcs1v15Encrypt)?;
+        self.import_window(timestamp_sec, entity)
+    }
+    ///导入 pkcs1 或 pkcs8 der 私钥
+    pub fn import_private_der(&mut self, timestamp_sec: i64, der: &[u8]) -> anyhow::Result<i64> {
+        let entity = RsaEntity::from_private_der(der, Pkcs1v15Encrypt)?;
+        self.import_window(timestamp_sec, entity)
+    }
+    ///导入 pkcs1 或 spki pem 公钥，该窗口只能验签和加密
+    pub fn import_public_pem(&mut self, timestamp_sec: i64, pem: &str) -> anyhow::Result<i64> {
+        let entity = RsaEntity::from_public_pem(pem, Pkcs1v15Encrypt)?;
+        self.import_window(timestamp_sec, entity)
+    }
+    ///导入 pkcs1 或 spki der 公钥，该窗口只能验签和加密
+    pub fn import_public_der(&mut self, timestamp_sec: i64, der: &[u8]) -> anyhow::Result<i64> {
+        let entity = RsaEntity::from_public_der(der, Pkcs1v15Encrypt)?;
+        self.import_window(timestamp_sec, entity)
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let windows = self.certs.len();
+        let private_keys = self
+            .certs
+            .values()
+            .filter(|cert| cert.has_private_key())
+            .count();
+        let key_bytes = self
+            .certs
+            .values()
+            .map(|cert| cert.key_heap_size())
+            .sum::<usize>();
+        let entry_size = std::mem::size_of::<(i64, RsaEntity<Pkcs1v15Encrypt>)>();
+        let total_bytes =
+            std::mem::size_of::<Self>() + self.certs.capacity() * entry_size + key_bytes;
+        MemoryUsage {
+            windows,
+            private_keys,
+            key_bytes,
+            total_bytes,
+        }
+    }
+
+    //工作区间内每个窗口的起始时间
+    pub(crate) fn windows<TZ: TimeZone>(
         work_range_utc_sec: (DateTime<TZ>, DateTime<TZ>),
         interval: i64,
-        bit_size: usize,
-    ) -> anyhow::Result<Vec<(i64, RsaEntity<Pkcs1v15Encrypt>)>> {
+    ) -> anyhow::Result<Vec<i64>> {
+        if interval <= 0 {
+            return anyhow::anyhow!("upgrade cycle must be at least one second").err();
+        }
         let start = work_range_utc_sec.0.timestamp();
         let end = work_range_utc_sec.1.timestamp();
-        let mut certs = vec![];
+        let mut windows = vec![];
         for i in 0..i64::MAX {
             if i * interval + start > end + interval {
                 break;
             }
-            let mut cn = start + i * interval;
-            cn -= cn % interval;
+            let cn = start + i * interval;
+            windows.push(window_start(cn, interval));
+        }
+        windows.ok()
+    }
+
+    pub(crate) fn generate_rsa_entity<Key: AsRef<[u8]>, TZ: TimeZone>(
+        key: Key,
+        work_range_utc_sec: (DateTime<TZ>, DateTime<TZ>),
+        interval: i64,
+        bit_size: usize,
+    ) -> anyhow::Result<Vec<(i64, RsaEntity<Pkcs1v15Encrypt>)>> {
+        let mut certs = vec![];
+        for cn in PassportEntity::windows(work_range_utc_sec, interval)? {
             let rsa_cert = RsaEntityBuilder::new(key.as_ref())
                 .set_bit_size(bit_size)
                 .set_confuse_number(cn)
@@ -53,52 +308,95 @@ impl PassportEntity {
         certs.ok()
     }
 
+    pub(crate) fn generate_random_rsa_entity<TZ: TimeZone>(
+        work_range_utc_sec: (DateTime<TZ>, DateTime<TZ>),
+        interval: i64,
+        bit_size: usize,
+    ) -> anyhow::Result<Vec<(i64, RsaEntity<Pkcs1v15Encrypt>)>> {
+        let mut certs = vec![];
+        for cn in PassportEntity::windows(work_range_utc_sec, interval)? {
+            let rsa_cert = RsaEntity::new(&mut OsRng, bit_size, Pkcs1v15Encrypt)?;
+            certs.push((cn, rsa_cert));
+        }
+        certs.ok()
+    }
+
+    fn window_of(&self, timestamp_sec: i64) -> i64 {
+        window_start(timestamp_sec, self.interval)
+    }
+
     pub(crate) fn get_rsa_entity(&self, timestamp_sec: i64) -> Option<&RsaEntity<Pkcs1v15Encrypt>> {
-        let ts = timestamp_sec - timestamp_sec % self.interval;
-        self.certs.get(&ts)
+        self.certs.get(&self.window_of(timestamp_sec))
     }
 
-    pub fn encrypt<D: AsRef<[u8]>>(
+    //window_id 必须是已有窗口的起始时间，不做取整
+    fn get_window(&self, window_id: i64) -> anyhow::Result<&RsaEntity<Pkcs1v15Encrypt>> {
+        match self.certs.get(&window_id) {
+            Some(s) => s.ok(),
+            None => anyhow::anyhow!("window[{}] does not exist", window_id).err(),
+        }
+    }
+
+    ///全部窗口的起始时间(窗口 id)，升序
+    pub fn window_ids(&self) -> Vec<i64> {
+        let mut windows: Vec<i64> = self.certs.keys().copied().collect();
+        windows.sort();
+        windows
+    }
+
+    ///用 window_id 指定的窗口签名，与 sign_sha256(data, 窗口内任一时间戳) 的结果相同
+    pub fn sign_with_window<D: AsRef<[u8]>>(
         &self,
+        window_id: i64,
         data: D,
-        timestamp_sec: i64,
     ) -> anyhow::Result<Vec<u8>> {
+        check_unbound(data.as_ref())?;
+        self.get_window(window_id)?.sign_sha256(data.as_ref())
+    }
+
+    ///用 window_id 指定的窗口验签，适合已知窗口的归档签名
+    pub fn verify_with_window<D: AsRef<[u8]>>(
+        &self,
+        window_id: i64,
+        data: D,
+        sign: D,
+    ) -> anyhow::Result<()> {
+        self.get_window(window_id)?
+            .verify_sha256(data.as_ref(), sign.as_ref())
+    }
+
+    #[cfg(feature = "encrypt")]
+    pub fn encrypt<D: AsRef<[u8]>>(&self, data: D, timestamp_sec: i64) -> anyhow::Result<Vec<u8>> {
         let re = match self.get_rsa_entity(timestamp_sec) {
             Some(s) => s,
-            None => {
-                return anyhow::anyhow!("timestamp[{}] Out of scope of verification", timestamp_sec)
-                    .err()
-            }
+            None => return anyhow::Error::from(PassportError::OutOfRange(timestamp_sec)).err(),
         };
         re.encrypt(data.as_ref())
     }
-    pub fn decrypt<D: AsRef<[u8]>>(
-        &self,
-        data: D,
-        timestamp_sec: i64,
-    ) -> anyhow::Result<Vec<u8>> {
+    #[cfg(feature = "encrypt")]
+    pub fn decrypt<D: AsRef<[u8]>>(&self, data: D, timestamp_sec: i64) -> anyhow::Result<Vec<u8>> {
         let re = match self.get_rsa_entity(timestamp_sec) {
             Some(s) => s,
-            None => {
-                return anyhow::anyhow!("timestamp[{}] Out of scope of verification", timestamp_sec)
-                    .err()
-            }
+            None => return anyhow::Error::from(PassportError::OutOfRange(timestamp_sec)).err(),
         };
         re.decrypt(data.as_ref())
     }
+    ///以 BOUND_SIGN_TAG 开头的 data 会被拒绝，这类数据只能用 sign_sha256_bound 签名
     pub fn sign_sha256<D: AsRef<[u8]>>(
         &self,
         data: D,
         timestamp_sec: i64,
     ) -> anyhow::Result<Vec<u8>> {
+        check_unbound(data.as_ref())?;
+        self.sign_raw(data.as_ref(), timestamp_sec)
+    }
+    //不检查 BOUND_SIGN_TAG，只给 sign_sha256 与 sign_sha256_bound 使用
+    fn sign_raw(&self, data: &[u8], timestamp_sec: i64) -> anyhow::Result<Vec<u8>> {
         let re = match self.get_rsa_entity(timestamp_sec) {
             Some(s) => s,
-            None => {
-                return anyhow::anyhow!("timestamp[{}] Out of scope of verification", timestamp_sec)
-                    .err()
-            }
+            None => return anyhow::Error::from(PassportError::OutOfRange(timestamp_sec)).err(),
         };
-        re.sign_sha256(data.as_ref())
+        re.sign_sha256(data)
     }
     pub fn verify_sha256<D: AsRef<[u8]>>(
         &self,
@@ -108,13 +406,45 @@ impl PassportEntity {
     ) -> anyhow::Result<()> {
         let re = match self.get_rsa_entity(timestamp_sec) {
             Some(s) => s,
-            None => {
-                return anyhow::anyhow!("timestamp[{}] Out of scope of verification", timestamp_sec)
-                    .err()
-            }
+            None => return anyhow::Error::from(PassportError::OutOfRange(timestamp_sec)).err(),
         };
         re.verify_sha256(data.as_ref(), sign.as_ref())
     }
+    ///签名 data 的同时绑定 timestamp_sec 本身与可选的 context，编码见 bound_message
+    ///
+    ///sign_sha256 只用时间戳选择窗口，签名在整个窗口内都有效；这里的签名只对同一个时间戳和 context 有效
+    pub fn sign_sha256_bound<D: AsRef<[u8]>>(
+        &self,
+        data: D,
+        timestamp_sec: i64,
+        context: Option<&str>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let message = bound_message(data.as_ref(), timestamp_sec, context)?;
+        self.sign_raw(message.as_slice(), timestamp_sec)
+    }
+    ///验证 sign_sha256_bound 的签名，timestamp_sec 与 context 必须与签名时相同
+    pub fn verify_sha256_bound<D: AsRef<[u8]>>(
+        &self,
+        data: D,
+        sign: D,
+        timestamp_sec: i64,
+        context: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let message = bound_message(data.as_ref(), timestamp_sec, context)?;
+        self.verify_sha256(message.as_slice(), sign.as_ref(), timestamp_sec)
+    }
+    ///用当前时钟所在的窗口签名，等同于 sign_sha256(data, self.now())
+    pub fn sign_now<D: AsRef<[u8]>>(&self, data: D) -> anyhow::Result<Vec<u8>> {
+        self.sign_sha256(data, self.now())
+    }
+    ///用当前时钟所在的窗口验签，窗口轮换后旧窗口的签名不再通过
+    pub fn verify_now<D: AsRef<[u8]>>(&self, data: D, sign: D) -> anyhow::Result<()> {
+        self.verify_sha256(data, sign, self.now())
+    }
+    #[cfg(feature = "encrypt")]
+    pub fn encrypt_now<D: AsRef<[u8]>>(&self, data: D) -> anyhow::Result<Vec<u8>> {
+        self.encrypt(data, self.now())
+    }
     pub fn to_public_pem(&self) -> Vec<(i64, String)> {
         let mut res = vec![];
         for (index, cert) in self.certs.iter() {
@@ -125,9 +455,225 @@ impl PassportEntity {
                     .expect("PassportEntity.to_public_pem error"),
             ))
         }
-        res.sort_by(|a, b| a.0.cmp(&b.0));
+        res.sort_by_key(|a| a.0);
+        res
+    }
+    ///与 to_public_pem 相同，每个窗口附带公钥指纹与 key id
+    pub fn to_public_pem_with_fingerprint(&self) -> Vec<(KeyFingerprint, String)> {
+        self.to_public_pem()
+            .into_iter()
+            .map(|(window, pem)| (self.fingerprints[&window].clone(), pem))
+            .collect()
+    }
+    ///每个窗口公钥的指纹与 key id，按窗口排序
+    pub fn to_public_fingerprint(&self) -> Vec<KeyFingerprint> {
+        let mut res: Vec<KeyFingerprint> = self.fingerprints.values().cloned().collect();
+        res.sort_by_key(|a| a.window);
         res
     }
+    ///JWK Set，kid 为 key id，window 为窗口起始时间
+    pub fn to_jwks(&self) -> anyhow::Result<Jwks> {
+        let mut keys = vec![];
+        for fp in self.to_public_fingerprint() {
+            let cert = &self.certs[&fp.window];
+            keys.push(cert.public_jwk()?.set_window(fp.window));
+        }
+        Jwks { keys }.ok()
+    }
+    ///按指纹或 key id 查找窗口起始时间，忽略大小写、":" 分隔符和 "sha256:" 前缀
+    pub fn window_by_fingerprint(&self, fingerprint: &str) -> Option<i64> {
+        let fingerprint = fingerprint.trim();
+        let fingerprint = fingerprint.strip_prefix("sha256:").unwrap_or(fingerprint);
+        let fingerprint: String = fingerprint
+            .chars()
+            .filter(|c| *c != ':')
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        match fingerprint.len() {
+            64 | 40 => self.by_fingerprint.get(&fingerprint).copied(),
+            _ => None,
+        }
+    }
+    ///按指纹或 key id 查找窗口证书
+    pub fn get_rsa_entity_by_fingerprint(
+        &self,
+        fingerprint: &str,
+    ) -> Option<&RsaEntity<Pkcs1v15Encrypt>> {
+        self.window_by_fingerprint(fingerprint)
+            .and_then(|w| self.certs.get(&w))
+    }
+    ///按窗口导出公钥，OpenSSH 格式的注释为 wd_passport@<窗口起始时间>
+    pub fn export_public_keys(
+        &self,
+        format: PublicKeyFormat,
+    ) -> anyhow::Result<Vec<(i64, Vec<u8>)>> {
+        let mut res = vec![];
+        for (index, cert) in self.certs.iter() {
+            let buf = match format {
+                PublicKeyFormat::OpenSsh => cert
+                    .public_openssh(format!("wd_passport@{}", index).as_str())?
+                    .into_bytes(),
+                _ => cert.export_public(format)?,
+            };
+            res.push((*index, buf));
+        }
+        res.sort_by_key(|a| a.0);
+        res.ok()
+    }
+    ///按窗口导出私钥，用于持久化随机模式生成的证书或导入到其他系统
+    pub fn export_private_keys(
+        &self,
+        format: PrivateKeyFormat,
+    ) -> anyhow::Result<Vec<(i64, Zeroizing<Vec<u8>>)>> {
+        let mut res = vec![];
+        for (index, cert) in self.certs.iter() {
+            res.push((*index, cert.export_private(format)?));
+        }
+        res.sort_by_key(|a| a.0);
+        res.ok()
+    }
+    ///每个窗口的自签名 X.509 证书 der，有效期为窗口的起止时间
+    ///
+    ///证书由窗口私钥签名，只导入了公钥的窗口没有私钥，会被跳过。
+    ///params 设置了与 subject 不同的 issuer 时，证书仍由窗口私钥签名，只是 issuer 名称不同，
+    ///严格来说不再是自签名证书，按 issuer 查找签发者的 X.509 工具会找不到它
+    pub fn to_x509_der(&self, params: &CertificateParams) -> anyhow::Result<Vec<(i64, Vec<u8>)>> {
+        let mut res = vec![];
+        for (index, cert) in self.certs.iter() {
+            if !cert.has_private_key() {
+                continue;
+            }
+            let der = cert.self_signed_certificate_der(
+                params.issuer_for(*index).as_str(),
+                params.subject_for(*index).as_str(),
+                *index,
+                *index + self.interval - 1,
+            )?;
+            res.push((*index, der));
+        }
+        res.sort_by_key(|a| a.0);
+        res.ok()
+    }
+    ///每个窗口的自签名 X.509 证书 pem
+    pub fn to_x509_pem(&self, params: &CertificateParams) -> anyhow::Result<Vec<(i64, String)>> {
+        let mut res = vec![];
+        for (index, der) in self.to_x509_der(params)? {
+            res.push((index, certificate_der_to_pem(der.as_slice())?));
+        }
+        res.ok()
+    }
+    ///设置根证书，subject 为根证书名称，例如 "CN=wd_passport root"
+    pub fn set_root_key(&mut self, root: RsaEntity<Pkcs1v15Encrypt>, subject: &str) {
+        self.root = Some(RootKey {
+            entity: root,
+            subject: subject.to_string(),
+        });
+    }
+    ///由 key 派生根证书，相同的 key 与 bit_size 总能得到相同的根
+    pub fn derive_root_key<Key: AsRef<[u8]>>(
+        &mut self,
+        key: Key,
+        bit_size: usize,
+        subject: &str,
+    ) -> anyhow::Result<()> {
+        let mut root_key = Zeroizing::new(Vec::with_capacity(
+            key.as_ref().len() + ROOT_KEY_DOMAIN.len(),
+        ));
+        root_key.extend_from_slice(key.as_ref());
+        root_key.extend_from_slice(ROOT_KEY_DOMAIN);
+        let root = RsaEntityBuilder::new(root_key.as_slice())
+            .set_bit_size(bit_size)
+            .set_confuse_number(0)
+            .build_pkcs15()?;
+        self.set_root_key(root, subject);
+        Ok(())
+    }
+    pub fn root_key(&self) -> Option<&RsaEntity<Pkcs1v15Encrypt>> {
+        self.root.as_ref().map(|r| &r.entity)
+    }
+    fn root(&self) -> anyhow::Result<&RootKey> {
+        match self.root.as_ref() {
+            Some(s) => Ok(s),
+            None => anyhow::anyhow!("root key not configured").err(),
+        }
+    }
+    ///自签名的根 CA 证书，有效期覆盖全部窗口
+    pub fn root_certificate_der(&self) -> anyhow::Result<Vec<u8>> {
+        let root = self.root()?;
+        let (first, last) = match (self.certs.keys().min(), self.certs.keys().max()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return anyhow::anyhow!("passport has no window").err(),
+        };
+        issue_certificate(
+            &root.entity,
+            IssueRequest {
+                issuer: root.subject.as_str(),
+                subject: root.subject.as_str(),
+                subject_key: root.entity.public_key(),
+                not_before_sec: first,
+                not_after_sec: last + self.interval - 1,
+                ca: true,
+                authority_key_id: None,
+            },
+        )
+    }
+    pub fn root_certificate_pem(&self) -> anyhow::Result<String> {
+        certificate_der_to_pem(self.root_certificate_der()?.as_slice())
+    }
+    ///由根证书签发的窗口证书 der，params.issuer 会被忽略，issuer 固定为根证书 subject
+    pub fn to_endorsed_x509_der(
+        &self,
+        params: &CertificateParams,
+    ) -> anyhow::Result<Vec<(i64, Vec<u8>)>> {
+        let root = self.root()?;
+        let authority_key_id = key_identifier(root.entity.public_key())?;
+        let mut res = vec![];
+        for (index, cert) in self.certs.iter() {
+            let der = issue_certificate(
+                &root.entity,
+                IssueRequest {
+                    issuer: root.subject.as_str(),
+                    subject: params.subject_for(*index).as_str(),
+                    subject_key: cert.public_key(),
+                    not_before_sec: *index,
+                    not_after_sec: *index + self.interval - 1,
+                    ca: false,
+                    authority_key_id: Some(authority_key_id.as_slice()),
+                },
+            )?;
+            res.push((*index, der));
+        }
+        res.sort_by_key(|a| a.0);
+        res.ok()
+    }
+    pub fn to_endorsed_x509_pem(
+        &self,
+        params: &CertificateParams,
+    ) -> anyhow::Result<Vec<(i64, String)>> {
+        let mut res = vec![];
+        for (index, der) in self.to_endorsed_x509_der(params)? {
+            res.push((index, certificate_der_to_pem(der.as_slice())?));
+        }
+        res.ok()
+    }
+    ///校验窗口证书由 verifier 固定的根签发后，导入其中的窗口公钥
+    pub fn import_endorsed_certificate(
+        &mut self,
+        verifier: &RootVerifier,
+        der: &[u8],
+    ) -> anyhow::Result<i64> {
+        let endorsed = verifier.verify_window_certificate_der(der)?;
+        if endorsed.window % self.interval != 0 {
+            return anyhow::anyhow!(
+                "certificate window[{}] does not match interval[{}]",
+                endorsed.window,
+                self.interval
+            )
+            .err();
+        }
+        let entity = RsaEntity::from_public_key(endorsed.public_key, Pkcs1v15Encrypt);
+        self.import_window(endorsed.window, entity)
+    }
     pub fn print_public_pem<Key: AsRef<[u8]>, TZ: TimeZone>(
         key: Key,
         work_range_utc_sec: (DateTime<TZ>, DateTime<TZ>),
@@ -151,18 +697,19 @@ impl PassportEntity {
 
 #[cfg(test)]
 mod test {
-    use super::PassportEntity;
+    use super::{KeyMode, PassportEntity};
     use crate::common::{Base64StdDecode, Base64StdEncode};
-    use chrono::{TimeZone, Utc};
+    use crate::PassportError;
+    use chrono::{NaiveDateTime, TimeZone, Utc};
     use std::time::Duration;
 
     #[test]
     fn test_entity_new() {
         let start = Utc::now();
         // let end = DateTime::from_str("2023-11-28T00:00:00").expect("截止时间生成错误");
-        let end = Utc
-            .datetime_from_str("2033-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
-            .expect("截止时间生成错误");
+        let end = NaiveDateTime::parse_from_str("2033-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("截止时间生成错误")
+            .and_utc();
         let interval = Duration::from_secs(60 * 60 * 24 * 30);
         let start_generate = std::time::Instant::now();
         let _entity = PassportEntity::new("hello world", (start, end), interval, 2048)
@@ -173,13 +720,183 @@ mod test {
             end_generate.as_secs()
         )
     }
+    #[test]
+    fn test_sign_with_window() {
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let entity = PassportEntity::new(
+            "hello world",
+            (
+                Utc.timestamp_opt(1675641600, 0).unwrap(),
+                Utc.timestamp_opt(1680739200, 0).unwrap(),
+            ),
+            interval,
+            1024,
+        )
+        .expect("证书生成失败");
+        let windows = entity.window_ids();
+        assert_eq!(windows, vec![1674432000, 1677024000, 1679616000]);
+        assert_eq!(
+            windows,
+            entity
+                .to_public_pem()
+                .into_iter()
+                .map(|x| x.0)
+                .collect::<Vec<_>>()
+        );
+
+        let window = windows[2];
+        let sign = entity
+            .sign_with_window(window, "hello world")
+            .expect("按窗口签名失败");
+        //与按时间戳选择窗口的签名相同，两种方式可以互相验证
+        assert_eq!(
+            sign,
+            entity
+                .sign_sha256("hello world", 1679919556)
+                .expect("签名失败")
+        );
+        entity
+            .verify_with_window(window, b"hello world".as_slice(), sign.as_slice())
+            .expect("按窗口验签失败");
+        entity
+            .verify_sha256(b"hello world".as_slice(), sign.as_slice(), window + 1)
+            .expect("按时间戳验签失败");
+        assert!(entity
+            .verify_with_window(windows[1], b"hello world".as_slice(), sign.as_slice())
+            .is_err());
+        assert!(entity
+            .verify_with_window(window, b"hello".as_slice(), sign.as_slice())
+            .is_err());
+        //窗口 id 必须是窗口起始时间，窗口内的其他时间戳不被接受
+        assert!(entity.sign_with_window(window + 1, "hello world").is_err());
+        assert!(entity.sign_with_window(0, "hello world").is_err());
+    }
+
+    #[test]
+    fn test_sign_bound() {
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let entity = PassportEntity::new(
+            "hello world",
+            (
+                Utc.timestamp_opt(1675641600, 0).unwrap(),
+                Utc.timestamp_opt(1680739200, 0).unwrap(),
+            ),
+            interval,
+            1024,
+        )
+        .expect("证书生成失败");
+        let timestamp = 1679919556;
+        let data = b"hello world".as_slice();
+        let sign = entity
+            .sign_sha256_bound(data, timestamp, Some("login"))
+            .expect("签名失败");
+        entity
+            .verify_sha256_bound(data, sign.as_slice(), timestamp, Some("login"))
+            .expect("验签失败");
+        //同一窗口内的其他时间戳、其他 context 都不能通过
+        assert!(entity
+            .verify_sha256_bound(data, sign.as_slice(), timestamp + 1, Some("login"))
+            .is_err());
+        assert!(entity
+            .verify_sha256_bound(data, sign.as_slice(), timestamp, Some("logout"))
+            .is_err());
+        assert!(entity
+            .verify_sha256_bound(data, sign.as_slice(), timestamp, None)
+            .is_err());
+        //与普通签名不能混用
+        assert!(entity
+            .verify_sha256(data, sign.as_slice(), timestamp)
+            .is_err());
+        let plain = entity.sign_sha256(data, timestamp).expect("签名失败");
+        assert!(entity
+            .verify_sha256_bound(data, plain.as_slice(), timestamp, None)
+            .is_err());
+        //签名的就是 bound_message
+        let message = crate::app::bound_message(data, timestamp, Some("login")).expect("编码失败");
+        entity
+            .verify_sha256(message.as_slice(), sign.as_slice(), timestamp)
+            .expect("bound_message 验签失败");
+        //普通签名不能伪造绑定签名
+        let e = entity
+            .sign_sha256(message.as_slice(), timestamp)
+            .unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<PassportError>(),
+            Some(&PassportError::BoundTag)
+        );
+        let window = entity.window_of(timestamp);
+        assert!(entity.sign_with_window(window, message.as_slice()).is_err());
+        let forged = entity.sign_sha256(crate::app::BOUND_SIGN_TAG, timestamp);
+        assert!(forged.is_err(), "只有标记的数据也不能普通签名");
+        assert!(entity.sign_sha256_bound(data, 1, None).is_err());
+    }
+
+    #[test]
+    fn test_sign_now() {
+        use crate::app::{Clock, FixedClock, OffsetClock};
+        use std::sync::Arc;
+
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let clock = Arc::new(FixedClock::new(1679919556));
+        let entity = PassportEntity::new(
+            "hello world",
+            (
+                Utc.timestamp_opt(1675641600, 0).unwrap(),
+                Utc.timestamp_opt(1680739200, 0).unwrap(),
+            ),
+            interval,
+            1024,
+        )
+        .expect("证书生成失败")
+        .with_clock(clock.clone());
+        assert_eq!(entity.now(), 1679919556);
+        let sign = entity.sign_now("hello world").expect("签名失败");
+        assert_eq!(
+            sign,
+            entity
+                .sign_sha256("hello world", 1679919556)
+                .expect("签名失败")
+        );
+        entity
+            .verify_now(b"hello world".as_slice(), sign.as_slice())
+            .expect("验签失败");
+        #[cfg(feature = "encrypt")]
+        {
+            let cipher = entity.encrypt_now("hello world").expect("加密失败");
+            assert_eq!(
+                entity.decrypt(cipher, 1679919556).expect("解密失败"),
+                b"hello world"
+            );
+        }
+
+        //时钟切换到其他窗口后，该签名不再通过
+        let window = entity.window_of(clock.now());
+        clock.set(window - 1);
+        assert!(entity
+            .verify_now(b"hello world".as_slice(), sign.as_slice())
+            .is_err());
+        //超出证书范围
+        clock.advance(interval.as_secs() as i64 * 10);
+        assert!(entity.sign_now("hello world").is_err());
+
+        //本机时间偏慢 60 秒
+        let mut entity = entity;
+        entity.set_clock(OffsetClock::new(FixedClock::new(window - 30), 60));
+        assert_eq!(entity.now(), window + 30);
+        entity
+            .verify_now(b"hello world".as_slice(), sign.as_slice())
+            .expect("偏移时钟验签失败");
+    }
+
     #[test]
     fn test_sign_verify() {
-        let start = Utc::now();
+        let start = NaiveDateTime::parse_from_str("2023-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("起始时间生成错误")
+            .and_utc();
         // let end = DateTime::from_str("2023-11-28T00:00:00").expect("截止时间生成错误");
-        let end = Utc
-            .datetime_from_str("2024-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
-            .expect("截止时间生成错误");
+        let end = NaiveDateTime::parse_from_str("2024-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("截止时间生成错误")
+            .and_utc();
         let interval = Duration::from_secs(60 * 60 * 24 * 30);
         let entity = PassportEntity::new("hello world", (start, end), interval, 1024)
             .expect("十年期证书生成失败");
@@ -194,23 +911,589 @@ mod test {
             .verify_sha256(data.as_bytes(), result.as_slice(), 1679919556)
             .expect("rsa 签名验证失败");
         println!("success");
+
+        //失败原因可以从 anyhow::Error 中 downcast 出来
+        let e = entity
+            .verify_sha256("hello".as_bytes(), result.as_slice(), 1679919556)
+            .unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<PassportError>(),
+            Some(&PassportError::BadSignature)
+        );
+        let e = entity.sign_sha256(data, 1).unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<PassportError>(),
+            Some(&PassportError::OutOfRange(1))
+        );
     }
 
     #[test]
-    fn test_encrypt_decrypt(){
-        let start = Utc::now();
-        let end = Utc
-            .datetime_from_str("2024-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
-            .expect("截止时间生成错误");
+    fn test_random_mode() {
+        let start = NaiveDateTime::parse_from_str("2023-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("起始时间生成错误")
+            .and_utc();
+        let end = NaiveDateTime::parse_from_str("2023-04-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("截止时间生成错误")
+            .and_utc();
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let a = PassportEntity::new_random((start, end), interval, 1024).expect("随机证书生成失败");
+        let b = PassportEntity::new_random((start, end), interval, 1024).expect("随机证书生成失败");
+        assert_eq!(a.mode(), KeyMode::Random);
+        assert_eq!(a.to_public_pem().len(), b.to_public_pem().len());
+        assert_ne!(
+            a.to_public_pem(),
+            b.to_public_pem(),
+            "随机模式不应生成相同证书"
+        );
+
+        let derived =
+            PassportEntity::new("hello world", (start, end), interval, 1024).expect("证书生成失败");
+        assert_eq!(derived.mode(), KeyMode::Derived);
+        assert_eq!(a.to_public_pem().len(), derived.to_public_pem().len());
+
+        let sign = a.sign_sha256("hello world", 1679919556).expect("签名错误");
+        a.verify_sha256("hello world".as_bytes(), sign.as_slice(), 1679919556)
+            .expect("随机证书签名验证失败");
+        assert!(b
+            .verify_sha256("hello world".as_bytes(), sign.as_slice(), 1679919556)
+            .is_err());
+    }
+
+    #[test]
+    fn test_memory_usage() {
+        let start = NaiveDateTime::parse_from_str("2023-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("起始时间生成错误")
+            .and_utc();
+        let end = NaiveDateTime::parse_from_str("2023-04-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("截止时间生成错误")
+            .and_utc();
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let entity =
+            PassportEntity::new("hello world", (start, end), interval, 1024).expect("证书生成失败");
+        let usage = entity.memory_usage();
+        assert_eq!(usage.windows, entity.to_public_pem().len());
+        assert_eq!(usage.private_keys, usage.windows);
+        //1024 位密钥：n、d 各 128 字节，p、q、dp、dq、qinv 各约 64 字节
+        assert!(usage.key_bytes >= usage.windows * (128 * 3 + 64 * 5));
+        assert!(usage.key_bytes <= usage.windows * (128 * 3 + 64 * 5 + 16));
+        assert!(usage.total_bytes > usage.key_bytes);
+    }
+
+    #[test]
+    fn test_export_keys() {
+        use crate::algo::{PrivateKeyFormat, PublicKeyFormat};
+        let start = NaiveDateTime::parse_from_str("2023-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("起始时间生成错误")
+            .and_utc();
+        let end = NaiveDateTime::parse_from_str("2023-04-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("截止时间生成错误")
+            .and_utc();
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let entity =
+            PassportEntity::new("hello world", (start, end), interval, 1024).expect("证书生成失败");
+        let windows: Vec<i64> = entity.to_public_pem().into_iter().map(|x| x.0).collect();
+
+        let list = entity
+            .export_public_keys(PublicKeyFormat::OpenSsh)
+            .expect("openssh 公钥导出失败");
+        assert_eq!(list.iter().map(|x| x.0).collect::<Vec<_>>(), windows);
+        for (window, line) in list {
+            let line = String::from_utf8(line).expect("openssh 公钥不是 utf8");
+            assert!(line.starts_with("ssh-rsa "));
+            assert!(line.ends_with(format!(" wd_passport@{}", window).as_str()));
+        }
+
+        let list = entity
+            .export_public_keys(PublicKeyFormat::Pkcs1Pem)
+            .expect("pkcs1 公钥导出失败");
+        let pem: Vec<(i64, String)> = list
+            .into_iter()
+            .map(|(i, buf)| (i, String::from_utf8(buf).expect("pem 不是 utf8")))
+            .collect();
+        assert_eq!(pem, entity.to_public_pem());
+
+        let list = entity
+            .export_private_keys(PrivateKeyFormat::Pkcs8Pem)
+            .expect("pkcs8 私钥导出失败");
+        assert_eq!(list.len(), windows.len());
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let start = NaiveDateTime::parse_from_str("2023-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("起始时间生成错误")
+            .and_utc();
+        let end = NaiveDateTime::parse_from_str("2023-04-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("截止时间生成错误")
+            .and_utc();
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let entity =
+            PassportEntity::new("hello world", (start, end), interval, 1024).expect("证书生成失败");
+        let list = entity.to_public_fingerprint();
+        let windows: Vec<i64> = entity.to_public_pem().into_iter().map(|x| x.0).collect();
+        assert_eq!(list.iter().map(|x| x.window).collect::<Vec<_>>(), windows);
+        for fp in list.iter() {
+            assert_eq!(fp.fingerprint.len(), 64);
+            assert!(fp.fingerprint.starts_with(fp.key_id.as_str()));
+            assert_eq!(
+                entity.window_by_fingerprint(fp.fingerprint.as_str()),
+                Some(fp.window)
+            );
+            assert_eq!(
+                entity.window_by_fingerprint(fp.key_id.as_str()),
+                Some(fp.window)
+            );
+            //兼容 openssl 风格的大写冒号分隔写法
+            let colon = fp
+                .fingerprint
+                .to_uppercase()
+                .as_bytes()
+                .chunks(2)
+                .map(|c| String::from_utf8_lossy(c).to_string())
+                .collect::<Vec<_>>()
+                .join(":");
+            assert_eq!(
+                entity.window_by_fingerprint(format!("sha256:{}", colon).as_str()),
+                Some(fp.window)
+            );
+            let cert = entity
+                .get_rsa_entity_by_fingerprint(fp.key_id.as_str())
+                .expect("按指纹查找证书失败");
+            assert_eq!(
+                cert.public_key(),
+                entity.get_rsa_entity(fp.window).unwrap().public_key()
+            );
+        }
+        assert_eq!(entity.window_by_fingerprint(""), None);
+        assert_eq!(entity.window_by_fingerprint(&"0".repeat(64)), None);
+        assert_eq!(entity.window_by_fingerprint(&list[0].key_id[..10]), None);
+
+        let jwks = entity
+            .to_jwks()
+            .and_then(|j| j.to_json())
+            .and_then(crate::algo::Jwks::from_json)
+            .expect("jwks 导出失败");
+        assert_eq!(jwks.keys.len(), list.len());
+        for (jwk, fp) in jwks.keys.iter().zip(list.iter()) {
+            assert_eq!(jwk.kid.as_deref(), Some(fp.key_id.as_str()));
+            assert_eq!(jwk.window, Some(fp.window));
+            assert_eq!(
+                &jwk.to_public_key().expect("jwk 公钥解析失败"),
+                entity.get_rsa_entity(fp.window).unwrap().public_key()
+            );
+        }
+
+        let with_fingerprint = entity.to_public_pem_with_fingerprint();
+        assert_eq!(
+            with_fingerprint
+                .iter()
+                .map(|(fp, pem)| (fp.window, pem.clone()))
+                .collect::<Vec<_>>(),
+            entity.to_public_pem()
+        );
+        assert_eq!(
+            with_fingerprint
+                .into_iter()
+                .map(|x| x.0)
+                .collect::<Vec<_>>(),
+            list
+        );
+
+        //替换窗口后，旧公钥的指纹不再能找到该窗口；同一公钥放入多个窗口时返回最早的窗口
+        let mut entity = entity;
+        let public = |entity: &PassportEntity, window: i64| {
+            let puk = entity.get_rsa_entity(window).unwrap().public_key().clone();
+            crate::algo::RsaEntity::from_public_key(puk, rsa::Pkcs1v15Encrypt)
+        };
+        entity
+            .import_window(list[2].window, public(&entity, list[0].window))
+            .expect("导入窗口失败");
+        assert_eq!(entity.window_by_fingerprint(list[2].key_id.as_str()), None);
+        assert_eq!(
+            entity.window_by_fingerprint(list[0].key_id.as_str()),
+            Some(list[0].window)
+        );
+        entity
+            .import_window(list[0].window, public(&entity, list[1].window))
+            .expect("导入窗口失败");
+        assert_eq!(
+            entity.window_by_fingerprint(list[0].key_id.as_str()),
+            Some(list[2].window)
+        );
+        assert_eq!(
+            entity.window_by_fingerprint(list[1].fingerprint.as_str()),
+            Some(list[0].window)
+        );
+        assert_eq!(entity.to_public_fingerprint()[2].key_id, list[0].key_id);
+    }
+
+    #[test]
+    fn test_import_keys() {
+        use crate::algo::RsaEntityBuilder;
+        let start = NaiveDateTime::parse_from_str("2023-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("起始时间生成错误")
+            .and_utc();
+        let end = NaiveDateTime::parse_from_str("2023-04-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("截止时间生成错误")
+            .and_utc();
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let mut entity =
+            PassportEntity::new("hello world", (start, end), interval, 1024).expect("证书生成失败");
+        let partner = RsaEntityBuilder::new("partner key")
+            .set_bit_size(1024)
+            .build_pkcs15()
+            .expect("rsa 证书生成失败");
+
+        //派生窗口之后的两个窗口：一个导入私钥，一个只导入公钥
+        let private_ts = 1686000000;
+        let public_ts = private_ts + 60 * 60 * 24 * 30;
+        let pem = partner.private_pkcs8_pem().expect("pkcs8 私钥导出失败");
+        let window = entity
+            .import_private_pem(private_ts, pem.as_str())
+            .expect("导入私钥失败");
+        assert_eq!(window, private_ts - private_ts % (60 * 60 * 24 * 30));
+        entity
+            .import_public_pem(
+                public_ts,
+                partner.public_spki_pem().expect("spki 导出失败").as_str(),
+            )
+            .expect("导入公钥失败");
+
+        assert_eq!(entity.window_source(1679919556), Some(KeyMode::Derived));
+        assert_eq!(entity.window_source(private_ts), Some(KeyMode::Imported));
+        assert_eq!(entity.window_source(public_ts), Some(KeyMode::Imported));
+        assert_eq!(entity.window_source(1), None);
+        let usage = entity.memory_usage();
+        assert_eq!(usage.private_keys + 1, usage.windows);
+
+        let data = "hello world";
+        let sign = entity
+            .sign_sha256(data, private_ts)
+            .expect("导入私钥签名失败");
+        partner
+            .verify_sha256(data.as_bytes(), sign.as_slice())
+            .expect("导入的私钥与原私钥不一致");
+        let sign = partner.sign_sha256(data.as_bytes()).expect("签名失败");
+        entity
+            .verify_sha256(data.as_bytes(), sign.as_slice(), public_ts)
+            .expect("导入公钥验签失败");
+        assert!(
+            entity.sign_sha256(data, public_ts).is_err(),
+            "公钥窗口不能签名"
+        );
+        assert!(entity
+            .verify_sha256(data.as_bytes(), sign.as_slice(), 1679919556)
+            .is_err());
+
+        let mut empty = PassportEntity::empty(interval).expect("空证书创建失败");
+        assert_eq!(empty.mode(), KeyMode::Imported);
+        empty
+            .import_private_der(
+                private_ts,
+                partner
+                    .private_pkcs8_der()
+                    .expect("der 导出失败")
+                    .as_slice(),
+            )
+            .expect("导入 der 私钥失败");
+        assert_eq!(empty.to_public_pem().len(), 1);
+    }
+
+    #[test]
+    fn test_x509() {
+        use crate::algo::CertificateParams;
+        use rsa::pkcs8::der::{Decode, Encode};
+        use x509_cert::ext::pkix::{KeyUsage, KeyUsages};
+        use x509_cert::Certificate;
+
+        let start = NaiveDateTime::parse_from_str("2023-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("起始时间生成错误")
+            .and_utc();
+        let end = NaiveDateTime::parse_from_str("2023-04-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("截止时间生成错误")
+            .and_utc();
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let entity =
+            PassportEntity::new("hello world", (start, end), interval, 1024).expect("证书生成失败");
+        let params = CertificateParams::new("CN=wd_passport {window},O=wd").set_issuer("CN=wd_ca");
+
+        let list = entity.to_x509_der(&params).expect("x509 证书生成失败");
+        assert_eq!(list.len(), entity.to_public_pem().len());
+        for (window, der) in list.iter() {
+            let cert = Certificate::from_der(der.as_slice()).expect("x509 证书解析失败");
+            let tbs = &cert.tbs_certificate;
+            let validity = tbs.validity;
+            assert_eq!(
+                validity.not_before.to_unix_duration().as_secs() as i64,
+                *window
+            );
+            assert_eq!(
+                validity.not_after.to_unix_duration().as_secs() as i64,
+                *window + 60 * 60 * 24 * 30 - 1
+            );
+            assert_eq!(
+                tbs.subject.to_string(),
+                format!("CN=wd_passport {},O=wd", window)
+            );
+            assert_eq!(tbs.issuer.to_string(), "CN=wd_ca");
+            let (critical, usage) = tbs
+                .get::<KeyUsage>()
+                .expect("key usage 解析失败")
+                .expect("缺少 key usage");
+            assert!(critical);
+            assert!(usage.0.contains(KeyUsages::DigitalSignature));
+            assert!(!usage.0.contains(KeyUsages::KeyEncipherment));
+
+            let tbs_der = tbs.to_vec().expect("tbs 编码失败");
+            entity
+                .verify_sha256(tbs_der.as_slice(), cert.signature.raw_bytes(), *window)
+                .expect("证书签名验证失败");
+        }
+
+        let pem = entity.to_x509_pem(&params).expect("x509 pem 生成失败");
+        assert!(pem[0].1.starts_with("-----BEGIN CERTIFICATE-----"));
+
+        //只有公钥的窗口无法签名证书，导出时跳过，其他窗口不受影响
+        let mut partial = PassportEntity::empty(interval).expect("空证书创建失败");
+        let public = entity.to_public_pem();
+        partial
+            .import_public_pem(public[0].0, public[0].1.as_str())
+            .expect("公钥导入失败");
+        let private = entity
+            .export_private_keys(crate::PrivateKeyFormat::Pkcs8Der)
+            .expect("私钥导出失败");
+        partial
+            .import_private_der(private[1].0, private[1].1.as_slice())
+            .expect("私钥导入失败");
+        let list = partial.to_x509_der(&params).expect("x509 证书生成失败");
+        assert_eq!(
+            list.iter().map(|x| x.0).collect::<Vec<_>>(),
+            vec![private[1].0]
+        );
+    }
+
+    #[test]
+    fn test_root_key() {
+        use crate::algo::{CertificateParams, RootVerifier};
+        let start = NaiveDateTime::parse_from_str("2023-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("起始时间生成错误")
+            .and_utc();
+        let end = NaiveDateTime::parse_from_str("2023-04-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("截止时间生成错误")
+            .and_utc();
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let mut entity =
+            PassportEntity::new("hello world", (start, end), interval, 1024).expect("证书生成失败");
+        assert!(entity.root_certificate_der().is_err(), "未设置根证书");
+        entity
+            .derive_root_key("hello world", 1024, "CN=wd_passport root")
+            .expect("根证书派生失败");
+
+        let mut other =
+            PassportEntity::new("hello world", (start, end), interval, 1024).expect("证书生成失败");
+        other
+            .derive_root_key("hello world", 1024, "CN=wd_passport root")
+            .expect("根证书派生失败");
+        assert_eq!(
+            entity.root_key().map(|r| r.public_key()),
+            other.root_key().map(|r| r.public_key()),
+            "相同 key 派生的根证书不一致"
+        );
+
+        let root_pem = entity.root_certificate_pem().expect("根证书生成失败");
+        let verifier = RootVerifier::from_certificate_pem(root_pem.as_str(), interval)
+            .expect("根证书校验失败");
+        let params = CertificateParams::default();
+        let endorsed = entity
+            .to_endorsed_x509_der(&params)
+            .expect("窗口证书签发失败");
+        let public = entity.to_public_pem();
+        assert_eq!(endorsed.len(), public.len());
+        let mut verify_only = PassportEntity::empty(interval).expect("空证书创建失败");
+        for (window, der) in endorsed.iter() {
+            let w = verifier
+                .verify_window_certificate_der(der.as_slice())
+                .expect("窗口证书校验失败");
+            assert_eq!(w.window, *window);
+            assert_eq!(
+                &w.public_key,
+                entity
+                    .get_rsa_entity(*window)
+                    .expect("缺少窗口")
+                    .public_key()
+            );
+            verify_only
+                .import_endorsed_certificate(&verifier, der.as_slice())
+                .expect("导入窗口证书失败");
+        }
+        let sign = entity
+            .sign_sha256("hello world", 1679919556)
+            .expect("签名错误");
+        verify_only
+            .verify_sha256("hello world".as_bytes(), sign.as_slice(), 1679919556)
+            .expect("根证书认可的窗口验签失败");
+
+        //根从未签发过的窗口证书会被拒绝
+        let self_signed = entity.to_x509_der(&params).expect("x509 证书生成失败");
+        assert!(verifier
+            .verify_window_certificate_der(self_signed[0].1.as_slice())
+            .is_err());
+        let mut stranger =
+            PassportEntity::new("another key", (start, end), interval, 1024).expect("证书生成失败");
+        stranger
+            .derive_root_key("another key", 1024, "CN=wd_passport root")
+            .expect("根证书派生失败");
+        let foreign = stranger
+            .to_endorsed_x509_der(&params)
+            .expect("窗口证书签发失败");
+        assert!(verifier
+            .verify_window_certificate_der(foreign[0].1.as_slice())
+            .is_err());
+        assert!(verify_only
+            .import_endorsed_certificate(&verifier, foreign[0].1.as_slice())
+            .is_err());
+    }
+
+    #[test]
+    fn test_root_verifier_constraints() {
+        use crate::algo::{issue_certificate, key_identifier, IssueRequest, RootVerifier};
+        use rsa::pkcs8::der::asn1::{BitStringRef, ObjectIdentifier};
+        use rsa::pkcs8::der::{Decode, Encode};
+        use x509_cert::ext::pkix::{KeyUsage, KeyUsages};
+        use x509_cert::Certificate;
+
+        let start = NaiveDateTime::parse_from_str("2023-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("起始时间生成错误")
+            .and_utc();
+        let end = NaiveDateTime::parse_from_str("2023-04-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("截止时间生成错误")
+            .and_utc();
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let mut entity =
+            PassportEntity::new("hello world", (start, end), interval, 1024).expect("证书生成失败");
+        entity
+            .derive_root_key("hello world", 1024, "CN=wd_passport root")
+            .expect("根证书派生失败");
+        let root = entity.root_key().expect("缺少根证书");
+        let aki = key_identifier(root.public_key()).expect("根公钥标识生成失败");
+        let pinned = RootVerifier::from_certificate_der(
+            entity
+                .root_certificate_der()
+                .expect("根证书生成失败")
+                .as_slice(),
+            interval,
+        )
+        .expect("根证书校验失败");
+        let bare = RootVerifier::new(root.public_key().clone(), "CN=wd_passport root", interval)
+            .expect("根公钥校验器创建失败");
+        let window = entity.window_ids()[0];
+        let step = interval.as_secs() as i64;
+        let issue = |issuer: &str, not_before: i64, not_after: i64| {
+            issue_certificate(
+                root,
+                IssueRequest {
+                    issuer,
+                    subject: "CN=wd_passport window",
+                    subject_key: entity.get_window(window).expect("缺少窗口").public_key(),
+                    not_before_sec: not_before,
+                    not_after_sec: not_after,
+                    ca: false,
+                    authority_key_id: Some(aki.as_slice()),
+                },
+            )
+            .expect("窗口证书签发失败")
+        };
+
+        let ok = issue("CN=wd_passport root", window, window + step - 1);
+        pinned
+            .verify_window_certificate_der(ok.as_slice())
+            .expect("窗口证书校验失败");
+        bare.verify_window_certificate_der(ok.as_slice())
+            .expect("窗口证书校验失败");
+        //只固定根公钥时也要校验 issuer
+        let other = RootVerifier::new(root.public_key().clone(), "CN=other root", interval)
+            .expect("根公钥校验器创建失败");
+        assert!(other.verify_window_certificate_der(ok.as_slice()).is_err());
+        let renamed = issue("CN=other root", window, window + step - 1);
+        assert!(pinned
+            .verify_window_certificate_der(renamed.as_slice())
+            .is_err());
+        //有效期必须是一个对齐的窗口
+        let unaligned = issue("CN=wd_passport root", window + 1, window + step);
+        assert!(bare
+            .verify_window_certificate_der(unaligned.as_slice())
+            .is_err());
+        let longer = issue("CN=wd_passport root", window, window + 2 * step - 1);
+        assert!(bare
+            .verify_window_certificate_der(longer.as_slice())
+            .is_err());
+        //超出根证书有效期的窗口，只有固定了根证书时才能发现
+        let last = *entity.window_ids().last().expect("没有窗口");
+        let later = issue("CN=wd_passport root", last + step, last + 2 * step - 1);
+        assert!(pinned
+            .verify_window_certificate_der(later.as_slice())
+            .is_err());
+        bare.verify_window_certificate_der(later.as_slice())
+            .expect("窗口证书校验失败");
+
+        //根签发的、可用于加密的证书也会被拒绝
+        let cert = Certificate::from_der(ok.as_slice()).expect("证书解析失败");
+        let usage = KeyUsage(KeyUsages::DigitalSignature | KeyUsages::KeyEncipherment)
+            .to_vec()
+            .expect("key usage 编码失败");
+        let mut tbs = cert.tbs_certificate.clone();
+        for ext in tbs.extensions.iter_mut().flatten() {
+            if ext.extn_id == ObjectIdentifier::new_unwrap("2.5.29.15") {
+                ext.extn_value = usage.as_slice();
+            }
+        }
+        let sign = root
+            .sign_sha256(tbs.to_vec().expect("tbs 编码失败").as_slice())
+            .expect("签名失败");
+        let forged = Certificate {
+            tbs_certificate: tbs,
+            signature_algorithm: cert.signature_algorithm,
+            signature: BitStringRef::from_bytes(sign.as_slice()).expect("签名编码失败"),
+        }
+        .to_vec()
+        .expect("证书编码失败");
+        assert!(pinned
+            .verify_window_certificate_der(forged.as_slice())
+            .is_err());
+        assert!(RootVerifier::new(root.public_key().clone(), "", Duration::ZERO).is_err());
+    }
+
+    #[cfg(feature = "encrypt")]
+    #[test]
+    fn test_encrypt_decrypt() {
+        let start = NaiveDateTime::parse_from_str("2023-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("起始时间生成错误")
+            .and_utc();
+        let end = NaiveDateTime::parse_from_str("2024-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("截止时间生成错误")
+            .and_utc();
         let interval = Duration::from_secs(60 * 60 * 24 * 30);
         let entity = PassportEntity::new("hello world", (start, end), interval, 1024)
             .expect("十年期证书生成失败");
 
         let data = b"hello world";
-        let ciphertext = entity.encrypt(&data, 1679919556).expect("encrypt failed");
-        println!("decrypt--->{:?}",ciphertext);
-        let plaintext = entity.decrypt(ciphertext.as_slice(),1679919556).expect("decrypt failed");
-        assert_eq!(&data[..],plaintext.as_slice(),"test_encrypt_decrypt failed");
+        let ciphertext = entity.encrypt(data, 1679919556).expect("encrypt failed");
+        println!("decrypt--->{:?}", ciphertext);
+        let plaintext = entity
+            .decrypt(ciphertext.as_slice(), 1679919556)
+            .expect("decrypt failed");
+        assert_eq!(
+            &data[..],
+            plaintext.as_slice(),
+            "test_encrypt_decrypt failed"
+        );
+        let e = entity
+            .decrypt(b"not a ciphertext".as_slice(), 1679919556)
+            .unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<PassportError>(),
+            Some(&PassportError::Decrypt)
+        );
     }
 }
 
@@ -1,3 +1,24 @@
+mod bound;
+mod clock;
+#[cfg(feature = "sign")]
 mod entity;
+#[cfg(feature = "sign")]
+mod snapshot;
+#[cfg(feature = "sign")]
+mod vectors;
+#[cfg(feature = "verify")]
+mod verifier;
 
-pub use entity::PassportEntity;
+pub use bound::{bound_message, BOUND_SIGN_TAG};
+pub use clock::{Clock, FixedClock, OffsetClock, SystemClock};
+#[cfg(feature = "sign")]
+pub use entity::{KeyFingerprint, KeyMode, MemoryUsage, PassportEntity};
+#[cfg(feature = "sign")]
+pub use snapshot::{KeyringSnapshot, WindowSnapshot, KEYRING_SNAPSHOT_VERSION};
+#[cfg(feature = "sign")]
+pub use vectors::{
+    GoldenVectors, PassportVector, RsaEntityVector, RuleRandVector, WindowVector,
+    GOLDEN_VECTORS_MESSAGE, GOLDEN_VECTORS_VERSION,
+};
+#[cfg(feature = "verify")]
+pub use verifier::PassportVerifier;
@@ -0,0 +1,155 @@
+use crate::algo::RsaEntity;
+use crate::app::PassportEntity;
+use crate::common::*;
+use rsa::Pkcs1v15Encrypt;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+///快照文件格式版本，字段含义变化时递增
+pub const KEYRING_SNAPSHOT_VERSION: u32 = 1;
+
+///快照中的一个窗口，public_key 为 SubjectPublicKeyInfo PEM
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowSnapshot {
+    pub window: i64,
+    pub fingerprint: String,
+    pub key_id: String,
+    pub public_key: String,
+    ///密码加密的 pkcs8 PEM，只导出公钥时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+}
+
+///PassportEntity 的快照，不包含 key，可分发给只需要验签的一方，
+///或带上加密私钥在没有 key 的机器上恢复完整的证书
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyringSnapshot {
+    pub version: u32,
+    ///窗口长度，单位秒
+    pub interval: u64,
+    pub windows: Vec<WindowSnapshot>,
+}
+
+impl KeyringSnapshot {
+    ///password 为 Some 时附带用该密码加密的私钥
+    pub fn from_entity(entity: &PassportEntity, password: Option<&[u8]>) -> anyhow::Result<Self> {
+        if password.map(|p| p.is_empty()).unwrap_or(false) {
+            return anyhow::anyhow!("snapshot password must not be empty").err();
+        }
+        let mut windows = vec![];
+        for fp in entity.to_public_fingerprint() {
+            let cert = match entity.get_rsa_entity(fp.window) {
+                Some(s) => s,
+                None => return anyhow::anyhow!("window[{}] not found", fp.window).err(),
+            };
+            let private_key = match password {
+                Some(p) if cert.has_private_key() => Some(cert.encrypted_private_pkcs8_pem(p)?),
+                _ => None,
+            };
+            windows.push(WindowSnapshot {
+                window: fp.window,
+                fingerprint: fp.fingerprint,
+                key_id: fp.key_id,
+                public_key: cert.public_spki_pem()?,
+                private_key,
+            });
+        }
+        Self {
+            version: KEYRING_SNAPSHOT_VERSION,
+            interval: entity.upgrade_cycle().as_secs(),
+            windows,
+        }
+        .ok()
+    }
+    ///恢复为导入模式的 PassportEntity，password 为 None 时只恢复公钥
+    pub fn restore(&self, password: Option<&[u8]>) -> anyhow::Result<PassportEntity> {
+        if self.version != KEYRING_SNAPSHOT_VERSION {
+            return anyhow::anyhow!("unsupported snapshot version[{}]", self.version).err();
+        }
+        let mut entity = PassportEntity::empty(Duration::from_secs(self.interval))?;
+        for w in self.windows.iter() {
+            let cert = match (w.private_key.as_ref(), password) {
+                (Some(pem), Some(p)) => {
+                    RsaEntity::from_encrypted_private_pem(pem.as_str(), p, Pkcs1v15Encrypt)?
+                }
+                _ => RsaEntity::from_public_pem(w.public_key.as_str(), Pkcs1v15Encrypt)?,
+            };
+            if cert.fingerprint()? != w.fingerprint {
+                return anyhow::anyhow!("window[{}] fingerprint mismatch", w.window).err();
+            }
+            if entity.import_window(w.window, cert)? != w.window {
+                return anyhow::anyhow!(
+                    "window[{}] does not match interval[{}]",
+                    w.window,
+                    self.interval
+                )
+                .err();
+            }
+        }
+        entity.ok()
+    }
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        let s = serde_json::to_string_pretty(self)?;
+        Ok(s)
+    }
+    pub fn from_json<S: AsRef<str>>(s: S) -> anyhow::Result<Self> {
+        let snapshot = serde_json::from_str(s.as_ref())?;
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::KeyringSnapshot;
+    use crate::app::PassportEntity;
+    use chrono::NaiveDateTime;
+    use std::time::Duration;
+
+    #[test]
+    fn test_snapshot() {
+        let start = NaiveDateTime::parse_from_str("2023-02-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("起始时间生成错误")
+            .and_utc();
+        let end = NaiveDateTime::parse_from_str("2023-04-06 00:00:00", "%Y-%m-%d %H:%M:%S")
+            .expect("截止时间生成错误")
+            .and_utc();
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let entity =
+            PassportEntity::new("hello world", (start, end), interval, 1024).expect("证书生成失败");
+        let sign = entity
+            .sign_sha256("hello world", 1679919556)
+            .expect("签名错误");
+
+        //只有公钥的快照只能验签
+        let snapshot = KeyringSnapshot::from_entity(&entity, None).expect("快照生成失败");
+        let json = snapshot.to_json().expect("快照序列化失败");
+        assert!(!json.contains("PRIVATE KEY"));
+        let public = KeyringSnapshot::from_json(json)
+            .and_then(|s| s.restore(None))
+            .expect("快照恢复失败");
+        assert_eq!(public.to_public_pem(), entity.to_public_pem());
+        public
+            .verify_sha256("hello world".as_bytes(), sign.as_slice(), 1679919556)
+            .expect("快照验签失败");
+        assert!(public.sign_sha256("hello world", 1679919556).is_err());
+
+        //带加密私钥的快照用密码恢复后可以签名
+        assert!(KeyringSnapshot::from_entity(&entity, Some(b"")).is_err());
+        let snapshot =
+            KeyringSnapshot::from_entity(&entity, Some(b"password")).expect("快照生成失败");
+        assert!(snapshot.windows.iter().all(|w| w.private_key.is_some()));
+        let restored = snapshot.restore(Some(b"password")).expect("快照恢复失败");
+        assert_eq!(
+            restored
+                .sign_sha256("hello world", 1679919556)
+                .expect("签名错误"),
+            sign
+        );
+        assert!(snapshot.restore(Some(b"wrong")).is_err());
+
+        //篡改过的公钥无法恢复
+        let mut tampered = snapshot.clone();
+        tampered.windows[0].public_key = snapshot.windows[1].public_key.clone();
+        assert!(tampered.restore(None).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+use crate::algo::{RsaEntityBuilder, RuleRandBuilder};
+use crate::app::PassportEntity;
+use crate::common::*;
+use chrono::{TimeZone, Utc};
+use rand::RngCore;
+use rsa::pkcs1::{EncodeRsaPublicKey, LineEnding};
+use rustc_serialize::hex::ToHex;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+///向量文件格式版本，字段含义变化时递增
+pub const GOLDEN_VECTORS_VERSION: u32 = 1;
+
+///签名向量使用的固定消息
+pub const GOLDEN_VECTORS_MESSAGE: &str = "wd_passport golden vector";
+
+///RuleRand 输出向量：secret + confuse number -> 前 N 个字节
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleRandVector {
+    pub secret: String,
+    pub confuse_number: i64,
+    ///fill_bytes 输出，hex 编码
+    pub output: String,
+}
+
+///RsaEntityBuilder::build_pkcs15 向量
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RsaEntityVector {
+    pub secret: String,
+    pub confuse_number: i64,
+    pub bit_size: usize,
+    ///pkcs1 pem，换行固定为 LF
+    pub public_key_pem: String,
+    ///SubjectPublicKeyInfo DER 的 sha256，hex 编码
+    pub public_key_fingerprint: String,
+    pub message: String,
+    ///sign_sha256(message)，base64 url safe 无填充
+    pub signature: String,
+}
+
+///PassportEntity::to_public_pem 中的一个窗口
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowVector {
+    pub window: i64,
+    pub public_key_fingerprint: String,
+}
+
+///PassportEntity 向量：secret + 时间表 -> 每个窗口的公钥指纹
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PassportVector {
+    pub secret: String,
+    pub start: i64,
+    pub end: i64,
+    pub interval: u64,
+    pub bit_size: usize,
+    pub windows: Vec<WindowVector>,
+}
+
+///固定的测试向量集合，供其他语言的实现校验互通性
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldenVectors {
+    pub version: u32,
+    pub rule_rand: Vec<RuleRandVector>,
+    pub rsa_entity: Vec<RsaEntityVector>,
+    pub passport: Vec<PassportVector>,
+}
+
+impl GoldenVectors {
+    ///按默认用例重新计算全部向量
+    pub fn generate() -> anyhow::Result<Self> {
+        let rule_rand = vec![
+            RuleRandVector::generate("hello world", 123456, 32),
+            RuleRandVector::generate("wd_passport_default_key", 0, 32),
+            RuleRandVector::generate("wd_passport_default_key", -1, 64),
+        ];
+        let rsa_entity = vec![
+            RsaEntityVector::generate("hello world", 1677024000, 1024)?,
+            RsaEntityVector::generate("hello world", 1677024000, 2048)?,
+            RsaEntityVector::generate("wd_passport_default_key", 1555200000, 1024)?,
+        ];
+        let passport = vec![PassportVector::generate(
+            "hello world",
+            1675641600,
+            1683331200,
+            60 * 60 * 24 * 30,
+            1024,
+        )?];
+        Self {
+            version: GOLDEN_VECTORS_VERSION,
+            rule_rand,
+            rsa_entity,
+            passport,
+        }
+        .ok()
+    }
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        let s = serde_json::to_string_pretty(self)?;
+        Ok(s)
+    }
+    pub fn from_json<S: AsRef<str>>(s: S) -> anyhow::Result<Self> {
+        let vectors = serde_json::from_str(s.as_ref())?;
+        Ok(vectors)
+    }
+}
+
+impl RuleRandVector {
+    pub fn generate(secret: &str, confuse_number: i64, len: usize) -> Self {
+        let mut rng = RuleRandBuilder::new(secret)
+            .confuse_key_i64(confuse_number)
+            .build();
+        let mut output = vec![0u8; len];
+        rng.fill_bytes(output.as_mut_slice());
+        Self {
+            secret: secret.to_string(),
+            confuse_number,
+            output: output.to_hex(),
+        }
+    }
+}
+
+impl RsaEntityVector {
+    pub fn generate(secret: &str, confuse_number: i64, bit_size: usize) -> anyhow::Result<Self> {
+        let entity = RsaEntityBuilder::new(secret)
+            .set_bit_size(bit_size)
+            .set_confuse_number(confuse_number)
+            .build_pkcs15()?;
+        let public_key_pem = entity.public_key().to_pkcs1_pem(LineEnding::LF)?;
+        let public_key_fingerprint = entity.fingerprint()?;
+        let signature = entity
+            .sign_sha256(GOLDEN_VECTORS_MESSAGE.as_bytes())?
+            .base64()?;
+        Self {
+            secret: secret.to_string(),
+            confuse_number,
+            bit_size,
+            public_key_pem,
+            public_key_fingerprint,
+            message: GOLDEN_VECTORS_MESSAGE.to_string(),
+            signature,
+        }
+        .ok()
+    }
+}
+
+impl PassportVector {
+    pub fn generate(
+        secret: &str,
+        start: i64,
+        end: i64,
+        interval: u64,
+        bit_size: usize,
+    ) -> anyhow::Result<Self> {
+        let (start_time, end_time) = match (
+            Utc.timestamp_opt(start, 0).single(),
+            Utc.timestamp_opt(end, 0).single(),
+        ) {
+            (Some(s), Some(e)) => (s, e),
+            _ => return anyhow::anyhow!("invalid range[{},{}]", start, end).err(),
+        };
+        let entity = PassportEntity::new(
+            secret,
+            (start_time, end_time),
+            Duration::from_secs(interval),
+            bit_size,
+        )?;
+        let windows = entity
+            .to_public_fingerprint()
+            .into_iter()
+            .map(|fp| WindowVector {
+                window: fp.window,
+                public_key_fingerprint: fp.fingerprint,
+            })
+            .collect();
+        Self {
+            secret: secret.to_string(),
+            start,
+            end,
+            interval,
+            bit_size,
+            windows,
+        }
+        .ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GoldenVectors;
+
+    const GOLDEN_VECTORS_PATH: &str =
+        concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/golden_vectors.json");
+
+    fn checked_in() -> GoldenVectors {
+        let s = std::fs::read_to_string(GOLDEN_VECTORS_PATH).expect("读取向量文件失败");
+        GoldenVectors::from_json(s).expect("向量文件格式错误")
+    }
+
+    #[test]
+    fn test_golden_vectors() {
+        let expect = checked_in();
+        let actual = GoldenVectors::generate().expect("生成向量失败");
+        assert_eq!(expect.version, actual.version, "向量文件版本不一致");
+        assert_eq!(expect.rule_rand, actual.rule_rand, "RuleRand 输出变化");
+        assert_eq!(
+            expect.rsa_entity, actual.rsa_entity,
+            "RsaEntity 密钥或签名变化"
+        );
+        assert_eq!(expect.passport, actual.passport, "PassportEntity 公钥变化");
+    }
+
+    #[test]
+    fn test_golden_vectors_signature_verify() {
+        use crate::common::Base64StdDecode;
+        use rsa::pkcs1::DecodeRsaPublicKey;
+        use rsa::pkcs1v15::{Signature, VerifyingKey};
+        use rsa::sha2::Sha256;
+        use rsa::signature::Verifier;
+        use rsa::RsaPublicKey;
+
+        //只依赖向量文件本身完成验签，和其他语言的验证方式一致
+        for v in checked_in().rsa_entity {
+            let puk =
+                RsaPublicKey::from_pkcs1_pem(v.public_key_pem.as_str()).expect("公钥解析失败");
+            let sign = v.signature.try_decode_base64().expect("签名base64解码失败");
+            let sign = Signature::try_from(sign.as_slice()).expect("签名格式错误");
+            VerifyingKey::<Sha256>::new_with_prefix(puk)
+                .verify(v.message.as_bytes(), &sign)
+                .expect("向量签名验证失败");
+        }
+    }
+
+    ///重新导出向量文件：cargo test export_golden_vectors -- --ignored
+    #[test]
+    #[ignore]
+    fn export_golden_vectors() {
+        let json = GoldenVectors::generate()
+            .and_then(|v| v.to_json())
+            .expect("生成向量失败");
+        std::fs::write(GOLDEN_VECTORS_PATH, json + "\n").expect("写入向量文件失败");
+    }
+}
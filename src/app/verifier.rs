@@ -0,0 +1,290 @@
+use crate::algo::Jwks;
+use crate::app::bound_message;
+use crate::common::{Base64StdDecode, PFErr, PFOk};
+use crate::PassportError;
+use rsa::pkcs1::{DecodeRsaPublicKey, EncodeRsaPublicKey};
+#[cfg(feature = "encrypt")]
+use rsa::rand_core::OsRng;
+use rsa::RsaPublicKey;
+#[cfg(feature = "encrypt")]
+use rsa::{Pkcs1v15Encrypt, PublicKey};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use wd_passport_core::OwnedVerifier;
+
+///只持有各窗口公钥的验签器，不依赖 key 与私钥派生，可在浏览器等环境中使用
+///
+///窗口划分与验签由 wd_passport_core 的 OwnedVerifier 完成，与 PassportEntity 和嵌入式设备上的结果相同；
+///RsaPublicKey 只用于加密与导出公钥。公钥最大 4096 位
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassportVerifier {
+    verifier: OwnedVerifier,
+    keys: BTreeMap<i64, RsaPublicKey>,
+}
+
+impl PassportVerifier {
+    fn new(interval: Duration, keys: BTreeMap<i64, RsaPublicKey>) -> anyhow::Result<Self> {
+        let mut verifier = OwnedVerifier::new(interval.as_secs())?;
+        for (window, puk) in keys.iter() {
+            verifier.insert(*window, puk.to_pkcs1_der()?.as_bytes().to_vec())?;
+        }
+        Self { verifier, keys }.ok()
+    }
+
+    ///由 PassportEntity::to_public_pem 的输出构建，pem 为 pkcs1 公钥
+    pub fn from_public_pem<S: AsRef<str>>(
+        pems: &[(i64, S)],
+        upgrade_cycle: Duration,
+    ) -> anyhow::Result<Self> {
+        let mut keys = BTreeMap::new();
+        for (window, pem) in pems {
+            keys.insert(*window, RsaPublicKey::from_pkcs1_pem(pem.as_ref())?);
+        }
+        Self::new(upgrade_cycle, keys)
+    }
+
+    ///由 PassportEntity::to_jwks 的输出构建，每个 key 必须带有 window
+    pub fn from_jwks(jwks: &Jwks, upgrade_cycle: Duration) -> anyhow::Result<Self> {
+        let mut keys = BTreeMap::new();
+        for jwk in jwks.keys.iter() {
+            let window = match jwk.window {
+                Some(w) => w,
+                None => return anyhow::anyhow!("jwk[{:?}] has no window", jwk.kid).err(),
+            };
+            keys.insert(window, jwk.to_public_key()?);
+        }
+        Self::new(upgrade_cycle, keys)
+    }
+
+    pub fn upgrade_cycle(&self) -> Duration {
+        Duration::from_secs(self.verifier.interval_sec())
+    }
+
+    ///全部窗口的起始时间，升序
+    pub fn windows(&self) -> Vec<i64> {
+        self.keys.keys().copied().collect()
+    }
+
+    ///timestamp_sec 所在窗口的起始时间，没有该窗口的公钥时返回 None
+    pub fn window_of(&self, timestamp_sec: i64) -> Option<i64> {
+        self.verifier.window_of(timestamp_sec)
+    }
+
+    ///timestamp_sec 所在窗口的结束时间(不含)，该时间戳签发的凭证在此之后过期
+    pub fn expires_at(&self, timestamp_sec: i64) -> Option<i64> {
+        self.window_of(timestamp_sec)
+            .map(|w| w + self.verifier.interval_sec() as i64)
+    }
+
+    pub fn public_key(&self, timestamp_sec: i64) -> Option<&RsaPublicKey> {
+        self.keys.get(&self.window_of(timestamp_sec)?)
+    }
+
+    #[cfg(feature = "encrypt")]
+    ///用 timestamp_sec 所在窗口的公钥加密，与 PassportEntity::encrypt 相同，可由持有该窗口私钥的一方解密
+    pub fn encrypt<D: AsRef<[u8]>>(&self, data: D, timestamp_sec: i64) -> anyhow::Result<Vec<u8>> {
+        let puk = match self.public_key(timestamp_sec) {
+            Some(s) => s,
+            None => return anyhow::Error::from(PassportError::OutOfRange(timestamp_sec)).err(),
+        };
+        let result = puk.encrypt(&mut OsRng, Pkcs1v15Encrypt, data.as_ref())?;
+        Ok(result)
+    }
+
+    pub fn verify_sha256<D: AsRef<[u8]>>(
+        &self,
+        data: D,
+        sign: D,
+        timestamp_sec: i64,
+    ) -> anyhow::Result<()> {
+        self.verifier
+            .verify_sha256(data.as_ref(), sign.as_ref(), timestamp_sec)
+            .map_err(PassportError::from)?;
+        Ok(())
+    }
+
+    ///验证 PassportEntity::sign_sha256_bound 的签名，timestamp_sec 与 context 必须与签名时相同
+    pub fn verify_sha256_bound<D: AsRef<[u8]>>(
+        &self,
+        data: D,
+        sign: D,
+        timestamp_sec: i64,
+        context: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let message = bound_message(data.as_ref(), timestamp_sec, context)?;
+        self.verify_sha256(message.as_slice(), sign.as_ref(), timestamp_sec)
+    }
+
+    ///签名为 base64 url safe 无填充编码，与命令行、HTTP 服务的输出一致
+    pub fn verify_sha256_base64<D: AsRef<[u8]>, S: AsRef<str>>(
+        &self,
+        data: D,
+        sign: S,
+        timestamp_sec: i64,
+    ) -> anyhow::Result<()> {
+        let sign = sign.as_ref().try_decode_base64()?;
+        self.verify_sha256(data.as_ref(), sign.as_slice(), timestamp_sec)
+    }
+}
+
+#[cfg(all(test, feature = "sign"))]
+mod test {
+    use crate::app::{PassportEntity, PassportVerifier};
+    use crate::common::Base64StdEncode;
+    use crate::PassportError;
+    use chrono::{TimeZone, Utc};
+    use std::time::Duration;
+
+    #[test]
+    fn test_verifier() {
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let entity = PassportEntity::new(
+            "hello world",
+            (
+                Utc.timestamp_opt(1675641600, 0).unwrap(),
+                Utc.timestamp_opt(1680739200, 0).unwrap(),
+            ),
+            interval,
+            1024,
+        )
+        .expect("证书生成失败");
+        let timestamp = 1679919556;
+        let sign = entity
+            .sign_sha256(b"hello world", timestamp)
+            .expect("签名失败");
+
+        let from_pem =
+            PassportVerifier::from_public_pem(entity.to_public_pem().as_slice(), interval)
+                .expect("由 pem 构建验签器失败");
+        let jwks = entity.to_jwks().expect("导出 jwks 失败");
+        let from_jwks =
+            PassportVerifier::from_jwks(&jwks, interval).expect("由 jwks 构建验签器失败");
+        assert_eq!(from_pem, from_jwks);
+        assert_eq!(
+            from_pem.windows(),
+            entity
+                .to_public_pem()
+                .into_iter()
+                .map(|x| x.0)
+                .collect::<Vec<_>>()
+        );
+
+        for verifier in [from_pem, from_jwks] {
+            verifier
+                .verify_sha256(b"hello world".as_slice(), sign.as_slice(), timestamp)
+                .expect("验签失败");
+            let encoded = sign.clone().base64().expect("base64 编码失败");
+            verifier
+                .verify_sha256_base64(b"hello world", encoded.as_str(), timestamp)
+                .expect("base64 签名验签失败");
+            let e = verifier
+                .verify_sha256(b"hello".as_slice(), sign.as_slice(), timestamp)
+                .unwrap_err();
+            assert_eq!(
+                e.downcast_ref::<PassportError>(),
+                Some(&PassportError::BadSignature)
+            );
+            assert!(verifier.window_of(1).is_none());
+            let e = verifier
+                .verify_sha256(b"hello world".as_slice(), sign.as_slice(), 1)
+                .unwrap_err();
+            assert_eq!(
+                e.downcast_ref::<PassportError>(),
+                Some(&PassportError::OutOfRange(1))
+            );
+            assert_eq!(
+                verifier.window_of(timestamp),
+                Some(timestamp - timestamp % interval.as_secs() as i64)
+            );
+            assert_eq!(
+                verifier.expires_at(timestamp),
+                verifier
+                    .window_of(timestamp)
+                    .map(|w| w + interval.as_secs() as i64)
+            );
+            assert_eq!(verifier.expires_at(1), None);
+            let bound = entity
+                .sign_sha256_bound(b"hello world", timestamp, Some("login"))
+                .expect("绑定时间戳签名失败");
+            verifier
+                .verify_sha256_bound(
+                    b"hello world".as_slice(),
+                    bound.as_slice(),
+                    timestamp,
+                    Some("login"),
+                )
+                .expect("绑定时间戳验签失败");
+            assert!(verifier
+                .verify_sha256_bound(
+                    b"hello world".as_slice(),
+                    bound.as_slice(),
+                    timestamp + 1,
+                    Some("login")
+                )
+                .is_err());
+            #[cfg(feature = "encrypt")]
+            {
+                let cipher = verifier
+                    .encrypt(b"hello world", timestamp)
+                    .expect("公钥加密失败");
+                assert_eq!(
+                    entity.decrypt(cipher, timestamp).expect("私钥解密失败"),
+                    b"hello world"
+                );
+                assert!(verifier.encrypt(b"hello world", 1).is_err());
+            }
+        }
+
+        let pems = entity.to_public_pem();
+        let misaligned = [(pems[0].0 + 1, pems[0].1.as_str())];
+        assert!(PassportVerifier::from_public_pem(&misaligned, interval).is_err());
+        assert!(PassportVerifier::from_public_pem(pems.as_slice(), Duration::ZERO).is_err());
+    }
+
+    //no_std 验签核心与 PassportEntity 的签名互通
+    #[cfg(feature = "embedded")]
+    #[test]
+    fn test_embedded_verifier() {
+        use crate::embedded::{Error, Verifier, WindowKey};
+        use crate::PublicKeyFormat;
+
+        let interval = Duration::from_secs(60 * 60 * 24 * 30);
+        let entity = PassportEntity::new(
+            "hello world",
+            (
+                Utc.timestamp_opt(1675641600, 0).unwrap(),
+                Utc.timestamp_opt(1680739200, 0).unwrap(),
+            ),
+            interval,
+            1024,
+        )
+        .expect("证书生成失败");
+        let ders = entity
+            .export_public_keys(PublicKeyFormat::Pkcs1Der)
+            .expect("导出 der 失败");
+        let keys: Vec<WindowKey> = ders
+            .iter()
+            .map(|(w, der)| WindowKey::new(*w, der.as_slice()))
+            .collect();
+        let verifier =
+            Verifier::new(keys.as_slice(), interval.as_secs()).expect("构建 no_std 验签器失败");
+        for (window, _) in ders.iter() {
+            let timestamp = window + 100;
+            let sign = entity
+                .sign_sha256(b"hello world", timestamp)
+                .expect("签名失败");
+            verifier
+                .verify_sha256(b"hello world", sign.as_slice(), timestamp)
+                .expect("no_std 验签失败");
+            assert_eq!(
+                verifier.verify_sha256(b"hello", sign.as_slice(), timestamp),
+                Err(Error::BadSignature)
+            );
+            assert_eq!(verifier.window_of(timestamp), Some(*window));
+        }
+        assert_eq!(
+            verifier.verify_sha256(b"hello world", b"", 1),
+            Err(Error::OutOfRange(1))
+        );
+    }
+}
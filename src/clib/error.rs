@@ -0,0 +1,195 @@
+//! C 接口的错误码与错误信息
+//!
+//! 错误码是稳定的，只会新增不会修改；错误描述保存在调用线程的 thread local 中，
+//! 由 passport_last_error 读取。调用成功会清空当前线程的错误
+use crate::PassportError;
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::fmt::Display;
+use std::os::raw::{c_char, c_int};
+use std::sync::RwLock;
+
+pub const PASSPORT_OK: c_int = 0;
+///其他错误
+pub const PASSPORT_ERR: c_int = -1;
+///空指针等非法参数
+pub const PASSPORT_ERR_INVALID_ARGUMENT: c_int = -2;
+///输出缓冲区不足，*out_len 为需要的字节数
+pub const PASSPORT_ERR_BUFFER_TOO_SMALL: c_int = -3;
+///时间戳不在任何窗口内
+pub const PASSPORT_ERR_OUT_OF_RANGE: c_int = -4;
+///签名与数据不匹配
+pub const PASSPORT_ERR_BAD_SIGNATURE: c_int = -5;
+///密文无法用该窗口的私钥解密
+pub const PASSPORT_ERR_DECRYPT: c_int = -6;
+///无法用 key 与时间表生成证书
+pub const PASSPORT_ERR_INIT: c_int = -7;
+
+///日志回调，每次调用失败时以错误码和 utf8 错误描述调用，message 只在回调期间有效
+pub type PassportLogCallback = Option<extern "C" fn(code: c_int, message: *const c_char)>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<(c_int, CString)>> = const { RefCell::new(None) };
+}
+
+static LOG_CALLBACK: RwLock<PassportLogCallback> = RwLock::new(None);
+
+#[derive(Debug)]
+pub(crate) struct FfiError {
+    pub code: c_int,
+    pub message: String,
+}
+
+impl FfiError {
+    pub(crate) fn new<M: Display>(code: c_int, message: M) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+    pub(crate) fn null(name: &str) -> Self {
+        Self::new(PASSPORT_ERR_INVALID_ARGUMENT, format!("{} is null", name))
+    }
+    //记录到当前线程并调用日志回调，返回错误码
+    pub(crate) fn report(self) -> c_int {
+        //错误描述中不应出现 0 字节，出现时替换掉以免被截断
+        let message = CString::new(self.message.replace('\0', " ")).unwrap_or_default();
+        //先释放读锁再调用，回调中可以重新注册回调或调用其他函数
+        let cb = LOG_CALLBACK.read().ok().and_then(|cb| *cb);
+        if let Some(cb) = cb {
+            cb(self.code, message.as_ptr());
+        }
+        LAST_ERROR.with(|e| *e.borrow_mut() = Some((self.code, message)));
+        self.code
+    }
+}
+
+//按 PassportError 分类，其他错误归为 PASSPORT_ERR
+impl From<anyhow::Error> for FfiError {
+    fn from(e: anyhow::Error) -> Self {
+        let code = match e.downcast_ref::<PassportError>() {
+            Some(PassportError::OutOfRange(_)) => PASSPORT_ERR_OUT_OF_RANGE,
+            Some(PassportError::BadSignature) => PASSPORT_ERR_BAD_SIGNATURE,
+            Some(PassportError::Decrypt) => PASSPORT_ERR_DECRYPT,
+            _ => PASSPORT_ERR,
+        };
+        Self::new(code, e)
+    }
+}
+
+pub(crate) fn clear_error() {
+    LAST_ERROR.with(|e| *e.borrow_mut() = None);
+}
+
+//把结果转换为返回码，成功时清空当前线程的错误
+pub(crate) fn finish(res: Result<(), FfiError>) -> c_int {
+    match res {
+        Ok(()) => {
+            clear_error();
+            PASSPORT_OK
+        }
+        Err(e) => e.report(),
+    }
+}
+
+///当前线程最近一次失败的错误描述，没有错误时返回 NULL
+///返回的指针在当前线程下一次调用本库函数之前有效，调用方不能释放
+#[no_mangle]
+pub extern "C" fn passport_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| match e.borrow().as_ref() {
+        Some((_, message)) => message.as_ptr(),
+        None => std::ptr::null(),
+    })
+}
+
+///当前线程最近一次失败的错误码，没有错误时返回 PASSPORT_OK
+#[no_mangle]
+pub extern "C" fn passport_last_error_code() -> c_int {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map(|x| x.0).unwrap_or(PASSPORT_OK))
+}
+
+///注册日志回调，传入 NULL 取消；回调可能在任意调用线程中执行
+#[no_mangle]
+pub extern "C" fn passport_set_log_callback(callback: PassportLogCallback) {
+    if let Ok(mut cb) = LOG_CALLBACK.write() {
+        *cb = callback;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::CStr;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    //日志回调是全局的，注册回调的测试需要串行执行
+    static LOG_CALLBACK_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_last_error() {
+        clear_error();
+        assert!(passport_last_error().is_null());
+        assert_eq!(passport_last_error_code(), PASSPORT_OK);
+        let code = FfiError::new(PASSPORT_ERR_OUT_OF_RANGE, "out\0of range").report();
+        assert_eq!(code, PASSPORT_ERR_OUT_OF_RANGE);
+        assert_eq!(passport_last_error_code(), PASSPORT_ERR_OUT_OF_RANGE);
+        let message = unsafe { CStr::from_ptr(passport_last_error()) };
+        assert_eq!(message.to_str().expect("错误信息不是 utf8"), "out of range");
+        //错误只保存在当前线程
+        std::thread::spawn(|| assert_eq!(passport_last_error_code(), PASSPORT_OK))
+            .join()
+            .expect("线程执行失败");
+        assert_eq!(finish(Ok(())), PASSPORT_OK);
+        assert!(passport_last_error().is_null());
+    }
+
+    static LOGGED: AtomicI32 = AtomicI32::new(PASSPORT_OK);
+
+    extern "C" fn log(code: c_int, message: *const c_char) {
+        let message = unsafe { CStr::from_ptr(message) };
+        if message.to_bytes() == b"log callback" {
+            LOGGED.store(code, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_log_callback() {
+        let _lock = LOG_CALLBACK_LOCK.lock();
+        passport_set_log_callback(Some(log));
+        FfiError::new(PASSPORT_ERR_DECRYPT, "log callback").report();
+        passport_set_log_callback(None);
+        assert_eq!(LOGGED.load(Ordering::SeqCst), PASSPORT_ERR_DECRYPT);
+        LOGGED.store(PASSPORT_OK, Ordering::SeqCst);
+        FfiError::new(PASSPORT_ERR_DECRYPT, "log callback").report();
+        assert_eq!(LOGGED.load(Ordering::SeqCst), PASSPORT_OK);
+    }
+
+    static REENTERED: AtomicI32 = AtomicI32::new(0);
+
+    //在回调中重新注册自己
+    extern "C" fn reregister(_code: c_int, message: *const c_char) {
+        let message = unsafe { CStr::from_ptr(message) };
+        if message.to_bytes() == b"reregister" {
+            passport_set_log_callback(Some(reregister));
+            REENTERED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_log_callback_reentrant() {
+        let _lock = LOG_CALLBACK_LOCK.lock();
+        passport_set_log_callback(Some(reregister));
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            FfiError::new(PASSPORT_ERR, "reregister").report();
+            FfiError::new(PASSPORT_ERR, "reregister").report();
+            let _ = tx.send(());
+        });
+        rx.recv_timeout(Duration::from_secs(10))
+            .expect("回调中注册回调发生死锁");
+        passport_set_log_callback(None);
+        assert_eq!(REENTERED.load(Ordering::SeqCst), 2);
+    }
+}
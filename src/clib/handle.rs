@@ -0,0 +1,368 @@
+//! 基于句柄的 C 接口，一个进程中可以同时持有多个证书
+//!
+//! passport_new 创建的句柄必须用 passport_free 释放；句柄只读，可以在多个线程中同时使用
+use crate::clib::error::{clear_error, finish, FfiError, PASSPORT_ERR, PASSPORT_ERR_INIT};
+use crate::clib::ops;
+use crate::clib::v2::{input, output};
+use crate::{PassportEntity, PassportError};
+use chrono::{TimeZone, Utc};
+use rsa::pkcs1::EncodeRsaPublicKey;
+use std::os::raw::c_int;
+use std::time::Duration;
+
+///不透明句柄
+pub struct PassportHandle {
+    entity: PassportEntity,
+}
+
+unsafe fn new_entity(
+    key: *const u8,
+    key_len: usize,
+    start_sec: i64,
+    end_sec: i64,
+    interval_sec: u64,
+    bits: usize,
+) -> Result<PassportEntity, FfiError> {
+    let key = input("key", key, key_len)?;
+    let range = match (
+        Utc.timestamp_opt(start_sec, 0).single(),
+        Utc.timestamp_opt(end_sec, 0).single(),
+    ) {
+        (Some(s), Some(e)) => (s, e),
+        _ => {
+            return Err(FfiError::new(
+                PASSPORT_ERR_INIT,
+                format!("invalid range[{},{}]", start_sec, end_sec),
+            ))
+        }
+    };
+    PassportEntity::new(key, range, Duration::from_secs(interval_sec), bits)
+        .map_err(|e| FfiError::new(PASSPORT_ERR_INIT, e))
+}
+
+unsafe fn entity<'a>(handle: *const PassportHandle) -> Result<&'a PassportEntity, FfiError> {
+    match handle.as_ref() {
+        Some(h) => Ok(&h.entity),
+        None => Err(FfiError::null("handle")),
+    }
+}
+
+unsafe fn call(
+    handle: *const PassportHandle,
+    data: *const u8,
+    data_len: usize,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+    f: impl FnOnce(&PassportEntity, &[u8]) -> Result<Vec<u8>, FfiError>,
+) -> c_int {
+    let res = entity(handle)
+        .and_then(|e| f(e, input("data", data, data_len)?))
+        .and_then(|buf| output(buf.as_slice(), out, out_cap, out_len));
+    finish(res)
+}
+
+///用 key 与时间表(unix 秒)重新生成全局证书，供 sign/verify 与 *_v2 接口使用
+#[no_mangle]
+pub extern "C" fn passport_init(
+    key: *const u8,
+    key_len: usize,
+    start_sec: i64,
+    end_sec: i64,
+    interval_sec: u64,
+    bits: usize,
+) -> c_int {
+    let res = unsafe { new_entity(key, key_len, start_sec, end_sec, interval_sec, bits) }
+        .and_then(|e| crate::set_default_passport(e).map_err(|e| FfiError::new(PASSPORT_ERR, e)));
+    finish(res)
+}
+
+///创建证书句柄，失败时返回 NULL，原因由 passport_last_error 取得
+#[no_mangle]
+pub extern "C" fn passport_new(
+    key: *const u8,
+    key_len: usize,
+    start_sec: i64,
+    end_sec: i64,
+    interval_sec: u64,
+    bits: usize,
+) -> *mut PassportHandle {
+    match unsafe { new_entity(key, key_len, start_sec, end_sec, interval_sec, bits) } {
+        Ok(entity) => {
+            clear_error();
+            Box::into_raw(Box::new(PassportHandle { entity }))
+        }
+        Err(e) => {
+            e.report();
+            std::ptr::null_mut()
+        }
+    }
+}
+
+///释放句柄，传入 NULL 时什么也不做
+#[no_mangle]
+pub extern "C" fn passport_free(handle: *mut PassportHandle) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe {
+        drop(Box::from_raw(handle));
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn passport_sign(
+    handle: *const PassportHandle,
+    data: *const u8,
+    data_len: usize,
+    timestamp: i64,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> c_int {
+    unsafe {
+        call(handle, data, data_len, out, out_cap, out_len, |e, data| {
+            ops::sign(e, data, timestamp)
+        })
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn passport_verify(
+    handle: *const PassportHandle,
+    data: *const u8,
+    data_len: usize,
+    sign: *const u8,
+    sign_len: usize,
+    timestamp: i64,
+) -> c_int {
+    let res = unsafe { verify(handle, data, data_len, sign, sign_len, timestamp) };
+    finish(res)
+}
+
+unsafe fn verify(
+    handle: *const PassportHandle,
+    data: *const u8,
+    data_len: usize,
+    sign: *const u8,
+    sign_len: usize,
+    timestamp: i64,
+) -> Result<(), FfiError> {
+    let entity = entity(handle)?;
+    let data = input("data", data, data_len)?;
+    let sign = input("sign", sign, sign_len)?;
+    ops::verify(entity, data, sign, timestamp)
+}
+
+#[no_mangle]
+pub extern "C" fn passport_encrypt(
+    handle: *const PassportHandle,
+    data: *const u8,
+    data_len: usize,
+    timestamp: i64,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> c_int {
+    unsafe {
+        call(handle, data, data_len, out, out_cap, out_len, |e, data| {
+            ops::encrypt(e, data, timestamp)
+        })
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn passport_decrypt(
+    handle: *const PassportHandle,
+    data: *const u8,
+    data_len: usize,
+    timestamp: i64,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> c_int {
+    unsafe {
+        call(handle, data, data_len, out, out_cap, out_len, |e, data| {
+            ops::decrypt(e, data, timestamp)
+        })
+    }
+}
+
+///按窗口顺序拼接的全部 pkcs1 公钥 PEM，不包含结尾的 0 字节
+#[no_mangle]
+pub extern "C" fn passport_export_public_pem(
+    handle: *const PassportHandle,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> c_int {
+    let res = unsafe { entity(handle) }.and_then(|entity| {
+        let pem: String = entity.to_public_pem().into_iter().map(|x| x.1).collect();
+        unsafe { output(pem.as_bytes(), out, out_cap, out_len) }
+    });
+    finish(res)
+}
+
+///timestamp 所在窗口的 pkcs1 公钥 PEM，不包含结尾的 0 字节
+#[no_mangle]
+pub extern "C" fn passport_window_public_pem(
+    handle: *const PassportHandle,
+    timestamp: i64,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> c_int {
+    let res = unsafe { entity(handle) }.and_then(|entity| {
+        let pem = match entity.get_rsa_entity(timestamp) {
+            Some(cert) => cert
+                .public_key()
+                .to_pkcs1_pem(Default::default())
+                .map_err(|e| FfiError::new(PASSPORT_ERR, e))?,
+            None => return Err(anyhow::Error::from(PassportError::OutOfRange(timestamp)).into()),
+        };
+        unsafe { output(pem.as_bytes(), out, out_cap, out_len) }
+    });
+    finish(res)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::clib::error::*;
+
+    const START: i64 = 1675641600;
+    const END: i64 = 1680739200;
+    const INTERVAL: u64 = 60 * 60 * 24 * 30;
+
+    #[test]
+    fn test_handle() {
+        let key = b"hello world";
+        let handle = passport_new(key.as_ptr(), key.len(), START, END, INTERVAL, 1024);
+        assert!(!handle.is_null());
+        let data = b"hello\0world";
+        let timestamp = 1679919556;
+
+        let mut sign = vec![0u8; 128];
+        let mut len = 0usize;
+        let res = passport_sign(
+            handle,
+            data.as_ptr(),
+            data.len(),
+            timestamp,
+            sign.as_mut_ptr(),
+            sign.len(),
+            &mut len,
+        );
+        assert_eq!(res, PASSPORT_OK);
+        assert_eq!(len, 128);
+        let res = passport_verify(
+            handle,
+            data.as_ptr(),
+            data.len(),
+            sign.as_ptr(),
+            len,
+            timestamp,
+        );
+        assert_eq!(res, PASSPORT_OK);
+        let res = passport_verify(handle, data.as_ptr(), 5, sign.as_ptr(), len, timestamp);
+        assert_eq!(res, PASSPORT_ERR_BAD_SIGNATURE);
+
+        let mut cipher = vec![0u8; 128];
+        let res = passport_encrypt(
+            handle,
+            data.as_ptr(),
+            data.len(),
+            timestamp,
+            cipher.as_mut_ptr(),
+            cipher.len(),
+            &mut len,
+        );
+        assert_eq!(res, PASSPORT_OK);
+        let mut plain = vec![0u8; 64];
+        let res = passport_decrypt(
+            handle,
+            cipher.as_ptr(),
+            len,
+            timestamp,
+            plain.as_mut_ptr(),
+            plain.len(),
+            &mut len,
+        );
+        assert_eq!(res, PASSPORT_OK);
+        assert_eq!(&plain[..len], data);
+
+        let res = passport_export_public_pem(handle, std::ptr::null_mut(), 0, &mut len);
+        assert_eq!(res, PASSPORT_ERR_BUFFER_TOO_SMALL);
+        let mut pem = vec![0u8; len];
+        let res = passport_export_public_pem(handle, pem.as_mut_ptr(), pem.len(), &mut len);
+        assert_eq!(res, PASSPORT_OK);
+        let pem = String::from_utf8(pem).expect("pem 不是 utf8");
+        assert_eq!(pem.matches("BEGIN RSA PUBLIC KEY").count(), 3);
+        let mut window = vec![0u8; 1024];
+        let res = passport_window_public_pem(
+            handle,
+            timestamp,
+            window.as_mut_ptr(),
+            window.len(),
+            &mut len,
+        );
+        assert_eq!(res, PASSPORT_OK);
+        assert!(pem.contains(std::str::from_utf8(&window[..len]).unwrap()));
+        let res =
+            passport_window_public_pem(handle, 1, window.as_mut_ptr(), window.len(), &mut len);
+        assert_eq!(res, PASSPORT_ERR_OUT_OF_RANGE);
+        passport_free(handle);
+
+        //句柄与全局证书互不影响，相同参数得到相同签名
+        let other = passport_new(key.as_ptr(), key.len(), START, END, INTERVAL, 1024);
+        let mut again = vec![0u8; 128];
+        let res = passport_sign(
+            other,
+            data.as_ptr(),
+            data.len(),
+            timestamp,
+            again.as_mut_ptr(),
+            again.len(),
+            &mut len,
+        );
+        assert_eq!(res, PASSPORT_OK);
+        assert_eq!(again, sign);
+        passport_free(other);
+        passport_free(std::ptr::null_mut());
+
+        let res = passport_sign(
+            std::ptr::null(),
+            data.as_ptr(),
+            data.len(),
+            timestamp,
+            again.as_mut_ptr(),
+            again.len(),
+            &mut len,
+        );
+        assert_eq!(res, PASSPORT_ERR_INVALID_ARGUMENT);
+        assert!(passport_new(key.as_ptr(), key.len(), START, END, 0, 1024).is_null());
+        assert_eq!(passport_last_error_code(), PASSPORT_ERR_INIT);
+    }
+
+    #[test]
+    fn test_init_global() {
+        let _lock = crate::test::DEFAULT_PASSPORT_LOCK.lock();
+        let _restore = crate::test::RestoreDefaultPassport;
+        let key = b"hello world";
+        let res = passport_init(key.as_ptr(), key.len(), START, END, INTERVAL, 1024);
+        assert_eq!(res, PASSPORT_OK);
+        let expect = PassportEntity::new(
+            "hello world",
+            (
+                Utc.timestamp_opt(START, 0).unwrap(),
+                Utc.timestamp_opt(END, 0).unwrap(),
+            ),
+            Duration::from_secs(INTERVAL),
+            1024,
+        )
+        .expect("证书生成失败");
+        assert_eq!(crate::format_public_pem(), expect.to_public_pem());
+        let res = passport_init(std::ptr::null(), 3, START, END, INTERVAL, 1024);
+        assert_eq!(res, PASSPORT_ERR_INVALID_ARGUMENT);
+    }
+}
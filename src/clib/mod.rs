@@ -0,0 +1,152 @@
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
+mod error;
+mod handle;
+mod ops;
+mod v2;
+
+pub use error::*;
+pub use handle::*;
+pub use v2::*;
+
+use crate::clib::error::{clear_error, FfiError};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_longlong};
+
+unsafe fn c_str<'a>(name: &str, ptr: *const c_char) -> Result<&'a [u8], FfiError> {
+    if ptr.is_null() {
+        return Err(FfiError::null(name));
+    }
+    Ok(CStr::from_ptr(ptr).to_bytes())
+}
+
+//v1 接口失败时统一返回 -1，具体原因由 passport_last_error_code 取得
+fn v1_finish(res: Result<usize, FfiError>) -> c_int {
+    match res {
+        Ok(len) => {
+            clear_error();
+            len as c_int
+        }
+        Err(e) => {
+            e.report();
+            -1
+        }
+    }
+}
+
+//以下为 v1 接口，参数按 C 字符串处理，新代码请使用 v2 接口
+///签名写入 sign，sign 中第一个 0 字节之前的长度即缓冲区大小，返回签名长度
+#[no_mangle]
+pub extern "C" fn sign(data: *const c_char, timestamp: c_longlong, sign: *mut c_char) -> c_int {
+    v1_finish(unsafe { sign_v1(data, timestamp, sign) })
+}
+
+unsafe fn sign_v1(
+    data: *const c_char,
+    timestamp: c_longlong,
+    sign: *mut c_char,
+) -> Result<usize, FfiError> {
+    let data = c_str("data", data)?;
+    let sign_len = c_str("sign", sign)?.len();
+    let result = ops::global(|e| ops::sign(e, data, timestamp))?;
+    let len = result.len();
+    if len > sign_len {
+        return Err(FfiError::new(
+            PASSPORT_ERR_BUFFER_TOO_SMALL,
+            format!("sign buffer too small, need {} bytes", len),
+        ));
+    }
+    let buf: &mut [u8] = std::slice::from_raw_parts_mut(sign as *mut u8, sign_len);
+    buf[..len].copy_from_slice(result.as_slice());
+    Ok(len)
+}
+
+///签名按 C 字符串读取，包含 0 字节的签名请使用 passport_verify_v2
+#[no_mangle]
+pub extern "C" fn verify(data: *const c_char, sign: *const c_char, timestamp: c_longlong) -> c_int {
+    v1_finish(unsafe { verify_v1(data, sign, timestamp) })
+}
+
+unsafe fn verify_v1(
+    data: *const c_char,
+    sign: *const c_char,
+    timestamp: c_longlong,
+) -> Result<usize, FfiError> {
+    let data = c_str("data", data)?;
+    let sign = c_str("sign", sign)?;
+    ops::global(|e| ops::verify(e, data, sign, timestamp))?;
+    Ok(0)
+}
+
+///密文写入 ciphertext，缓冲区不足时截断，返回写入的长度
+#[no_mangle]
+pub extern "C" fn encrypt(
+    data: *const c_char,
+    timestamp: c_longlong,
+    ciphertext: *mut c_char,
+) -> c_int {
+    v1_finish(unsafe { encrypt_v1(data, timestamp, ciphertext) })
+}
+
+unsafe fn encrypt_v1(
+    data: *const c_char,
+    timestamp: c_longlong,
+    ciphertext: *mut c_char,
+) -> Result<usize, FfiError> {
+    let data = c_str("data", data)?;
+    let ciphertext_len = c_str("ciphertext", ciphertext)?.len();
+    let result = ops::global(|e| ops::encrypt(e, data, timestamp))?;
+    //沿用 v1 行为：缓冲区不足时截断，返回写入的长度
+    let len = result.len().min(ciphertext_len);
+    let buf: &mut [u8] = std::slice::from_raw_parts_mut(ciphertext as *mut u8, ciphertext_len);
+    buf[..len].copy_from_slice(&result[..len]);
+    Ok(len)
+}
+
+///解密 plaintext 中的密文，明文写入 data，缓冲区不足时截断，返回写入的长度
+#[no_mangle]
+pub extern "C" fn decrypt(
+    plaintext: *const c_char,
+    data: *const c_char,
+    timestamp: c_longlong,
+) -> c_int {
+    v1_finish(unsafe { decrypt_v1(plaintext, data, timestamp) })
+}
+
+unsafe fn decrypt_v1(
+    plaintext: *const c_char,
+    data: *const c_char,
+    timestamp: c_longlong,
+) -> Result<usize, FfiError> {
+    let plaintext = c_str("plaintext", plaintext)?;
+    let data_len = c_str("data", data)?.len();
+    let result = ops::global(|e| ops::decrypt(e, plaintext, timestamp))?;
+    let len = result.len().min(data_len);
+    let buf: &mut [u8] = std::slice::from_raw_parts_mut(data as *mut u8, data_len);
+    buf[..len].copy_from_slice(&result[..len]);
+    Ok(len)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_v1_last_error() {
+        let data = b"hello world\0";
+        let mut sign = [b'x'; 513];
+        sign[512] = 0;
+        let res = super::sign(
+            data.as_ptr() as *const c_char,
+            1,
+            sign.as_mut_ptr() as *mut c_char,
+        );
+        assert_eq!(res, -1);
+        assert_eq!(passport_last_error_code(), PASSPORT_ERR_OUT_OF_RANGE);
+        let res = super::verify(std::ptr::null(), data.as_ptr() as *const c_char, 1);
+        assert_eq!(res, -1);
+        assert_eq!(passport_last_error_code(), PASSPORT_ERR_INVALID_ARGUMENT);
+        let message = unsafe { CStr::from_ptr(passport_last_error()) };
+        assert_eq!(message.to_bytes(), b"data is null");
+    }
+}
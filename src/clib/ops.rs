@@ -0,0 +1,46 @@
+//! C 接口共用的证书操作，失败由 FfiError::from 按 PassportError 分类
+use crate::clib::error::{FfiError, PASSPORT_ERR};
+use crate::PassportEntity;
+
+pub(crate) fn sign(
+    entity: &PassportEntity,
+    data: &[u8],
+    timestamp: i64,
+) -> Result<Vec<u8>, FfiError> {
+    Ok(entity.sign_sha256(data, timestamp)?)
+}
+
+pub(crate) fn verify(
+    entity: &PassportEntity,
+    data: &[u8],
+    sign: &[u8],
+    timestamp: i64,
+) -> Result<(), FfiError> {
+    Ok(entity.verify_sha256(data, sign, timestamp)?)
+}
+
+pub(crate) fn encrypt(
+    entity: &PassportEntity,
+    data: &[u8],
+    timestamp: i64,
+) -> Result<Vec<u8>, FfiError> {
+    Ok(entity.encrypt(data, timestamp)?)
+}
+
+pub(crate) fn decrypt(
+    entity: &PassportEntity,
+    data: &[u8],
+    timestamp: i64,
+) -> Result<Vec<u8>, FfiError> {
+    Ok(entity.decrypt(data, timestamp)?)
+}
+
+//在全局证书上执行 f
+pub(crate) fn global<T>(
+    f: impl FnOnce(&PassportEntity) -> Result<T, FfiError>,
+) -> Result<T, FfiError> {
+    match crate::define::with_default_passport(f) {
+        Ok(res) => res,
+        Err(e) => Err(FfiError::new(PASSPORT_ERR, e)),
+    }
+}
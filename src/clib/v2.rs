@@ -0,0 +1,257 @@
+//! v2 C ABI：输入为 (ptr, len)，输出为 (out, out_cap, *out_len)，数据中可以包含 0 字节
+//!
+//! 输出缓冲区不足时返回 PASSPORT_ERR_BUFFER_TOO_SMALL，并把需要的字节数写入 *out_len，
+//! 调用方可以按该长度重新分配后再次调用
+use crate::clib::error::{finish, FfiError, PASSPORT_ERR_BUFFER_TOO_SMALL};
+use crate::clib::ops;
+use std::os::raw::c_int;
+
+//len 为 0 时允许传入空指针
+pub(crate) unsafe fn input<'a>(
+    name: &str,
+    ptr: *const u8,
+    len: usize,
+) -> Result<&'a [u8], FfiError> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if ptr.is_null() {
+        return Err(FfiError::null(name));
+    }
+    Ok(std::slice::from_raw_parts(ptr, len))
+}
+
+//把结果写入调用方的缓冲区，out_len 始终返回结果的实际长度
+pub(crate) unsafe fn output(
+    data: &[u8],
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> Result<(), FfiError> {
+    if out_len.is_null() {
+        return Err(FfiError::null("out_len"));
+    }
+    *out_len = data.len();
+    if data.len() > out_cap {
+        return Err(FfiError::new(
+            PASSPORT_ERR_BUFFER_TOO_SMALL,
+            format!("output buffer too small, need {} bytes", data.len()),
+        ));
+    }
+    if data.is_empty() {
+        return Ok(());
+    }
+    if out.is_null() {
+        return Err(FfiError::null("out"));
+    }
+    std::ptr::copy_nonoverlapping(data.as_ptr(), out, data.len());
+    Ok(())
+}
+
+unsafe fn call(
+    data: *const u8,
+    data_len: usize,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+    f: impl FnOnce(&[u8]) -> Result<Vec<u8>, FfiError>,
+) -> c_int {
+    let res = input("data", data, data_len)
+        .and_then(f)
+        .and_then(|buf| output(buf.as_slice(), out, out_cap, out_len));
+    finish(res)
+}
+
+///用全局证书签名，签名长度等于 rsa 位数 / 8
+#[no_mangle]
+pub extern "C" fn passport_sign_v2(
+    data: *const u8,
+    data_len: usize,
+    timestamp: i64,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> c_int {
+    unsafe {
+        call(data, data_len, out, out_cap, out_len, |data| {
+            ops::global(|e| ops::sign(e, data, timestamp))
+        })
+    }
+}
+
+///用全局证书验签，成功返回 PASSPORT_OK
+#[no_mangle]
+pub extern "C" fn passport_verify_v2(
+    data: *const u8,
+    data_len: usize,
+    sign: *const u8,
+    sign_len: usize,
+    timestamp: i64,
+) -> c_int {
+    let res = unsafe { verify(data, data_len, sign, sign_len, timestamp) };
+    finish(res)
+}
+
+unsafe fn verify(
+    data: *const u8,
+    data_len: usize,
+    sign: *const u8,
+    sign_len: usize,
+    timestamp: i64,
+) -> Result<(), FfiError> {
+    let data = input("data", data, data_len)?;
+    let sign = input("sign", sign, sign_len)?;
+    ops::global(|e| ops::verify(e, data, sign, timestamp))
+}
+
+#[no_mangle]
+pub extern "C" fn passport_encrypt_v2(
+    data: *const u8,
+    data_len: usize,
+    timestamp: i64,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> c_int {
+    unsafe {
+        call(data, data_len, out, out_cap, out_len, |data| {
+            ops::global(|e| ops::encrypt(e, data, timestamp))
+        })
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn passport_decrypt_v2(
+    data: *const u8,
+    data_len: usize,
+    timestamp: i64,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> c_int {
+    unsafe {
+        call(data, data_len, out, out_cap, out_len, |data| {
+            ops::global(|e| ops::decrypt(e, data, timestamp))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::clib::error::*;
+    use crate::test::DEFAULT_PASSPORT_LOCK;
+
+    #[test]
+    fn test_v2_sign_verify() {
+        let _lock = DEFAULT_PASSPORT_LOCK.lock();
+        //数据中间有 0 字节
+        let data = b"hello\0world";
+        let timestamp = 1866248975;
+        let mut len = 0usize;
+        let res = passport_sign_v2(
+            data.as_ptr(),
+            data.len(),
+            timestamp,
+            std::ptr::null_mut(),
+            0,
+            &mut len,
+        );
+        assert_eq!(res, PASSPORT_ERR_BUFFER_TOO_SMALL);
+        assert!(len > 0);
+        let mut sign = vec![0u8; len];
+        let res = passport_sign_v2(
+            data.as_ptr(),
+            data.len(),
+            timestamp,
+            sign.as_mut_ptr(),
+            sign.len(),
+            &mut len,
+        );
+        assert_eq!(res, PASSPORT_OK);
+        assert_eq!(len, sign.len());
+        assert_eq!(
+            sign,
+            crate::rsa_sha256_sign(data, timestamp).expect("签名错误")
+        );
+        let res = passport_verify_v2(data.as_ptr(), data.len(), sign.as_ptr(), len, timestamp);
+        assert_eq!(res, PASSPORT_OK);
+        //截断在 0 字节处的数据不能通过验签
+        let res = passport_verify_v2(data.as_ptr(), 5, sign.as_ptr(), len, timestamp);
+        assert_eq!(res, PASSPORT_ERR_BAD_SIGNATURE);
+        assert_eq!(passport_last_error_code(), PASSPORT_ERR_BAD_SIGNATURE);
+        let res = passport_verify_v2(std::ptr::null(), 3, sign.as_ptr(), len, timestamp);
+        assert_eq!(res, PASSPORT_ERR_INVALID_ARGUMENT);
+        let res = passport_sign_v2(
+            data.as_ptr(),
+            data.len(),
+            timestamp,
+            sign.as_mut_ptr(),
+            sign.len(),
+            std::ptr::null_mut(),
+        );
+        assert_eq!(res, PASSPORT_ERR_INVALID_ARGUMENT);
+        //带绑定标记的数据不能普通签名
+        let tagged = crate::bound_message(data, timestamp, None).expect("编码失败");
+        let res = passport_sign_v2(
+            tagged.as_ptr(),
+            tagged.len(),
+            timestamp,
+            sign.as_mut_ptr(),
+            sign.len(),
+            &mut len,
+        );
+        assert_eq!(res, PASSPORT_ERR);
+    }
+
+    #[test]
+    fn test_v2_encrypt_decrypt() {
+        let _lock = DEFAULT_PASSPORT_LOCK.lock();
+        let data = b"\0\x01binary\0";
+        let timestamp = 1866248975;
+        let mut cipher = vec![0u8; 512];
+        let mut len = 0usize;
+        let res = passport_encrypt_v2(
+            data.as_ptr(),
+            data.len(),
+            timestamp,
+            cipher.as_mut_ptr(),
+            cipher.len(),
+            &mut len,
+        );
+        assert_eq!(res, PASSPORT_OK);
+        cipher.truncate(len);
+        let mut plain = vec![0u8; 4];
+        let res = passport_decrypt_v2(
+            cipher.as_ptr(),
+            cipher.len(),
+            timestamp,
+            plain.as_mut_ptr(),
+            plain.len(),
+            &mut len,
+        );
+        assert_eq!(res, PASSPORT_ERR_BUFFER_TOO_SMALL);
+        assert_eq!(len, data.len());
+        plain.resize(len, 0);
+        let res = passport_decrypt_v2(
+            cipher.as_ptr(),
+            cipher.len(),
+            timestamp,
+            plain.as_mut_ptr(),
+            plain.len(),
+            &mut len,
+        );
+        assert_eq!(res, PASSPORT_OK);
+        assert_eq!(plain.as_slice(), data);
+        let res = passport_decrypt_v2(
+            cipher.as_ptr(),
+            cipher.len(),
+            1,
+            plain.as_mut_ptr(),
+            plain.len(),
+            &mut len,
+        );
+        assert_eq!(res, PASSPORT_ERR_OUT_OF_RANGE);
+        assert!(!passport_last_error().is_null());
+    }
+}
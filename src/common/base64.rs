@@ -1,20 +1,24 @@
 use ::base64::Engine;
 use std::panic;
 
+#[cfg(any(feature = "sign", feature = "verify"))]
 pub trait Base64StdEncode {
     fn base64(self) -> anyhow::Result<String>;
 }
 
+#[cfg(any(feature = "sign", feature = "verify"))]
 pub trait Base64StdDecode {
     fn try_decode_base64(self) -> anyhow::Result<Vec<u8>>;
 }
 
+#[cfg(any(feature = "sign", feature = "verify"))]
 impl<T: AsRef<[u8]> + panic::UnwindSafe> Base64StdEncode for T {
     fn base64(self) -> anyhow::Result<String> {
         encode(self.as_ref())
     }
 }
 
+#[cfg(any(feature = "sign", feature = "verify"))]
 impl<T: AsRef<[u8]>> Base64StdDecode for T {
     fn try_decode_base64(self) -> anyhow::Result<Vec<u8>> {
         decode(self.as_ref())
@@ -35,3 +39,9 @@ pub fn decode<T: AsRef<[u8]>>(data: T) -> anyhow::Result<Vec<u8>> {
     let buf = ::base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(data)?;
     Ok(buf)
 }
+
+//带填充的标准 base64，OpenSSH 等工具使用该格式
+#[cfg(feature = "sign")]
+pub fn encode_standard<T: AsRef<[u8]>>(data: T) -> String {
+    ::base64::engine::general_purpose::STANDARD.encode(data)
+}
@@ -1,7 +1,7 @@
 mod base64;
-mod hash;
+#[cfg(any(feature = "sign", feature = "verify"))]
 mod pf;
 
 pub use self::base64::*;
-pub use hash::MD5;
+#[cfg(any(feature = "sign", feature = "verify"))]
 pub use pf::*;
@@ -1,4 +1,4 @@
-/// Point-Free
+//! Point-Free
 
 pub trait PFOk<Err> {
     #[inline]
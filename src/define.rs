@@ -1,24 +1,26 @@
 use crate::common::PFErr;
-use crate::PassportEntity;
-use chrono::{TimeZone, Utc};
+use crate::{Clock, KeyFingerprint, PassportEntity};
+use chrono::NaiveDateTime;
 use lazy_static::lazy_static;
 use std::ops::DerefMut;
 use std::sync::RwLock;
 use std::time::Duration;
 
 lazy_static! {
-    static ref DEFAULT_PASSPORT: RwLock<PassportEntity> = {
-        let start = Utc
-            .datetime_from_str("2023-02-07 00:00:00", "%Y-%m-%d %H:%M:%S")
-            .expect("default passport entity,start time generate error");
-        let end = Utc
-            .datetime_from_str("2123-02-07 00:00:00", "%Y-%m-%d %H:%M:%S")
-            .expect("default passport entity,end time generate error");
-        let interval = Duration::from_secs(60 * 60 * 24 * 30 * 12 * 10);
-        let entity = PassportEntity::new("wd_passport_default_key", (start, end), interval, 1024)
-            .expect("rsa certs build failed");
-        RwLock::new(entity)
-    };
+    static ref DEFAULT_PASSPORT: RwLock<PassportEntity> = RwLock::new(default_entity());
+}
+
+//未调用 init_passport/set_default_passport 时使用的全局证书
+pub(crate) fn default_entity() -> PassportEntity {
+    let start = NaiveDateTime::parse_from_str("2023-02-07 00:00:00", "%Y-%m-%d %H:%M:%S")
+        .expect("default passport entity,start time generate error")
+        .and_utc();
+    let end = NaiveDateTime::parse_from_str("2123-02-07 00:00:00", "%Y-%m-%d %H:%M:%S")
+        .expect("default passport entity,end time generate error")
+        .and_utc();
+    let interval = Duration::from_secs(60 * 60 * 24 * 30 * 12 * 10);
+    PassportEntity::new("wd_passport_default_key", (start, end), interval, 1024)
+        .expect("rsa certs build failed")
 }
 
 pub fn init_passport(
@@ -28,59 +30,104 @@ pub fn init_passport(
     end: &str,
     interval: u64,
 ) -> anyhow::Result<()> {
-    let start = Utc.datetime_from_str(start, "%Y-%m-%d %H:%M:%S")?;
-    let end = Utc.datetime_from_str(end, "%Y-%m-%d %H:%M:%S")?;
+    let start = NaiveDateTime::parse_from_str(start, "%Y-%m-%d %H:%M:%S")?.and_utc();
+    let end = NaiveDateTime::parse_from_str(end, "%Y-%m-%d %H:%M:%S")?.and_utc();
     let interval = Duration::from_secs(interval);
     let entity = PassportEntity::new(key, (start, end), interval, bit_size)?;
+    set_default_passport(entity)
+}
+
+///替换全局证书
+pub fn set_default_passport(entity: PassportEntity) -> anyhow::Result<()> {
     let mut wpp = match DEFAULT_PASSPORT.write() {
         Ok(o) => o,
-        Err(e) => return anyhow::anyhow!("set DEFAULT_PASSPORT error:{}", e.to_string()).err(),
+        Err(e) => return anyhow::anyhow!("set DEFAULT_PASSPORT error:{}", e).err(),
     };
     (*wpp.deref_mut()) = entity;
     Ok(())
 }
 
-pub fn rsa_sha256_encrypt(data: &[u8], timestamp_sec: i64) -> anyhow::Result<Vec<u8>>{
-    let rpp = match DEFAULT_PASSPORT.read() {
+///替换全局证书的时钟，影响 rsa_sha256_sign_now/verify_now/encrypt_now
+pub fn set_default_clock<C: Clock + 'static>(clock: C) -> anyhow::Result<()> {
+    let mut wpp = match DEFAULT_PASSPORT.write() {
         Ok(o) => o,
-        Err(e) => {
-            return anyhow::anyhow!("encrypt.read DEFAULT_PASSPORT error:{}", e.to_string()).err()
-        }
+        Err(e) => return anyhow::anyhow!("set DEFAULT_PASSPORT clock error:{}", e).err(),
     };
-    rpp.encrypt(data, timestamp_sec)
+    wpp.set_clock(clock);
+    Ok(())
 }
 
-pub fn rsa_sha256_decrypt(data: &[u8], timestamp_sec: i64) -> anyhow::Result<Vec<u8>>{
+///在全局证书上执行 f，执行期间持有读锁，f 中不能再替换全局证书
+pub fn with_default_passport<T>(f: impl FnOnce(&PassportEntity) -> T) -> anyhow::Result<T> {
     let rpp = match DEFAULT_PASSPORT.read() {
         Ok(o) => o,
-        Err(e) => {
-            return anyhow::anyhow!("decrypt.read DEFAULT_PASSPORT error:{}", e.to_string()).err()
-        }
+        Err(e) => return anyhow::anyhow!("read DEFAULT_PASSPORT error:{}", e).err(),
     };
-    rpp.decrypt(data, timestamp_sec)
+    Ok(f(&rpp))
+}
+
+#[cfg(feature = "encrypt")]
+pub fn rsa_sha256_encrypt(data: &[u8], timestamp_sec: i64) -> anyhow::Result<Vec<u8>> {
+    with_default_passport(|rpp| rpp.encrypt(data, timestamp_sec))?
+}
+
+#[cfg(feature = "encrypt")]
+pub fn rsa_sha256_decrypt(data: &[u8], timestamp_sec: i64) -> anyhow::Result<Vec<u8>> {
+    with_default_passport(|rpp| rpp.decrypt(data, timestamp_sec))?
 }
 
 pub fn rsa_sha256_sign(data: &[u8], timestamp_sec: i64) -> anyhow::Result<Vec<u8>> {
-    let rpp = match DEFAULT_PASSPORT.read() {
-        Ok(o) => o,
-        Err(e) => {
-            return anyhow::anyhow!("sign.read DEFAULT_PASSPORT error:{}", e.to_string()).err()
-        }
-    };
-    rpp.sign_sha256(data, timestamp_sec)
+    with_default_passport(|rpp| rpp.sign_sha256(data, timestamp_sec))?
 }
 pub fn rsa_sha156_verify(data: &[u8], sign: &[u8], timestamp_sec: i64) -> anyhow::Result<()> {
-    let rpp = match DEFAULT_PASSPORT.read() {
-        Ok(o) => o,
-        Err(e) => {
-            return anyhow::anyhow!("verify.read DEFAULT_PASSPORT error:{}", e.to_string()).err()
-        }
-    };
-    rpp.verify_sha256(data, sign, timestamp_sec)
+    with_default_passport(|rpp| rpp.verify_sha256(data, sign, timestamp_sec))?
+}
+pub fn rsa_sha256_sign_with_window(window_id: i64, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    with_default_passport(|rpp| rpp.sign_with_window(window_id, data))?
+}
+pub fn rsa_sha256_verify_with_window(
+    window_id: i64,
+    data: &[u8],
+    sign: &[u8],
+) -> anyhow::Result<()> {
+    with_default_passport(|rpp| rpp.verify_with_window(window_id, data, sign))?
+}
+///绑定时间戳与 context 的签名，见 PassportEntity::sign_sha256_bound
+pub fn rsa_sha256_sign_bound(
+    data: &[u8],
+    timestamp_sec: i64,
+    context: Option<&str>,
+) -> anyhow::Result<Vec<u8>> {
+    with_default_passport(|rpp| rpp.sign_sha256_bound(data, timestamp_sec, context))?
+}
+pub fn rsa_sha256_verify_bound(
+    data: &[u8],
+    sign: &[u8],
+    timestamp_sec: i64,
+    context: Option<&str>,
+) -> anyhow::Result<()> {
+    with_default_passport(|rpp| rpp.verify_sha256_bound(data, sign, timestamp_sec, context))?
+}
+///用全局证书的时钟签名，见 PassportEntity::sign_now
+pub fn rsa_sha256_sign_now(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    with_default_passport(|rpp| rpp.sign_now(data))?
+}
+pub fn rsa_sha256_verify_now(data: &[u8], sign: &[u8]) -> anyhow::Result<()> {
+    with_default_passport(|rpp| rpp.verify_now(data, sign))?
+}
+#[cfg(feature = "encrypt")]
+pub fn rsa_sha256_encrypt_now(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    with_default_passport(|rpp| rpp.encrypt_now(data))?
+}
+///全局证书的全部窗口 id，升序
+pub fn default_window_ids() -> anyhow::Result<Vec<i64>> {
+    with_default_passport(PassportEntity::window_ids)
+}
+///与 format_public_pem 相同，每个窗口附带公钥指纹与 key id
+pub fn format_public_pem_with_fingerprint() -> anyhow::Result<Vec<(KeyFingerprint, String)>> {
+    with_default_passport(PassportEntity::to_public_pem_with_fingerprint)
 }
 pub fn format_public_pem() -> Vec<(i64, String)> {
-    let rpp = DEFAULT_PASSPORT
-        .read()
-        .expect("format_public_pem DEFAULT_PASSPORT error");
-    rpp.to_public_pem()
+    with_default_passport(PassportEntity::to_public_pem)
+        .expect("format_public_pem DEFAULT_PASSPORT error")
 }
@@ -0,0 +1,42 @@
+use std::fmt::{Display, Formatter};
+
+///PassportEntity/PassportVerifier 返回的错误类型，可以从 anyhow::Error 中 downcast 出来
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassportError {
+    ///时间戳不在任何窗口内
+    OutOfRange(i64),
+    ///签名与数据不匹配
+    BadSignature,
+    ///密文无法用该窗口的私钥解密
+    Decrypt,
+    ///普通签名的数据以 BOUND_SIGN_TAG 开头，这类数据只能用 sign_sha256_bound 签名
+    BoundTag,
+}
+
+impl Display for PassportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PassportError::OutOfRange(ts) => {
+                write!(f, "timestamp[{}] Out of scope of verification", ts)
+            }
+            PassportError::BadSignature => write!(f, "signature verification failed"),
+            PassportError::Decrypt => write!(f, "decryption failed"),
+            PassportError::BoundTag => write!(
+                f,
+                "data starting with BOUND_SIGN_TAG can only be signed by sign_sha256_bound"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PassportError {}
+
+//验签核心的错误：验签只会返回 OutOfRange 与 BadSignature，公钥表的错误在加入公钥时已经返回，其余按签名错误处理
+impl From<wd_passport_core::Error> for PassportError {
+    fn from(e: wd_passport_core::Error) -> Self {
+        match e {
+            wd_passport_core::Error::OutOfRange(ts) => PassportError::OutOfRange(ts),
+            _ => PassportError::BadSignature,
+        }
+    }
+}
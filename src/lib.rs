@@ -1,20 +1,73 @@
+#[cfg(all(unix, feature = "sign"))]
+mod agent;
+#[cfg(any(feature = "sign", feature = "verify"))]
 mod algo;
+#[cfg(any(feature = "sign", feature = "verify"))]
 mod app;
+#[cfg(feature = "ffi")]
 mod clib;
 mod common;
+#[cfg(feature = "global")]
 mod define;
+mod error;
 
+#[cfg(all(unix, feature = "sign"))]
+pub use agent::*;
+#[cfg(any(feature = "sign", feature = "verify"))]
 pub use algo::*;
-pub use app::PassportEntity;
+#[cfg(feature = "verify")]
+pub use app::PassportVerifier;
+#[cfg(any(feature = "sign", feature = "verify"))]
+pub use app::{bound_message, Clock, FixedClock, OffsetClock, SystemClock, BOUND_SIGN_TAG};
+#[cfg(feature = "sign")]
+pub use app::{
+    GoldenVectors, KeyFingerprint, KeyMode, KeyringSnapshot, MemoryUsage, PassportEntity,
+    PassportVector, RsaEntityVector, RuleRandVector, WindowSnapshot, WindowVector,
+    GOLDEN_VECTORS_MESSAGE, GOLDEN_VECTORS_VERSION, KEYRING_SNAPSHOT_VERSION,
+};
+#[cfg(feature = "sign")]
 pub use chrono::Utc;
+#[cfg(feature = "ffi")]
 pub use clib::*;
+pub use common::{decode, encode};
+#[cfg(feature = "global")]
 pub use define::*;
+pub use error::PassportError;
+#[cfg(feature = "embedded")]
+pub use wd_passport_core as embedded;
+pub use wd_passport_core::window_start;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "global"))]
 mod test {
-    use crate::{format_public_pem, init_passport, rsa_sha156_verify, rsa_sha256_sign};
+    use crate::{
+        default_window_ids, format_public_pem, init_passport, rsa_sha156_verify, rsa_sha256_sign,
+        rsa_sha256_sign_bound, rsa_sha256_sign_now, rsa_sha256_sign_with_window,
+        rsa_sha256_verify_bound, rsa_sha256_verify_now, rsa_sha256_verify_with_window,
+        set_default_clock, set_default_passport, FixedClock, PassportEntity,
+    };
+    use chrono::{TimeZone, Utc};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    //默认证书是全局的，会修改它的测试需要串行执行
+    pub(crate) static DEFAULT_PASSPORT_LOCK: Mutex<()> = Mutex::new(());
+
+    //drop 时恢复内置的全局证书，测试失败时同样生效；需要在 DEFAULT_PASSPORT_LOCK 之后声明
+    pub(crate) struct RestoreDefaultPassport;
+
+    impl Drop for RestoreDefaultPassport {
+        fn drop(&mut self) {
+            let res = set_default_passport(crate::define::default_entity());
+            if !std::thread::panicking() {
+                res.expect("passport entity init error");
+            }
+        }
+    }
+
     #[test]
     fn test_default_init_passport() {
+        let _lock = DEFAULT_PASSPORT_LOCK.lock();
+        let _restore = RestoreDefaultPassport;
         let key = "hello world".as_bytes();
         init_passport(
             key,
@@ -48,10 +101,76 @@ mod test {
 
     #[test]
     fn test_default_passport_sign_verify() {
+        let _lock = DEFAULT_PASSPORT_LOCK.lock();
         let data = "hello world";
         let timestamp = 1866248975;
         let sign = rsa_sha256_sign(data.as_bytes(), timestamp).expect("sign error");
         rsa_sha156_verify(data.as_bytes(), sign.as_slice(), timestamp).expect("verify error");
         println!("test_default_passport_sign_verify  success");
     }
+
+    #[test]
+    fn test_default_passport_sign_with_window() {
+        let _lock = DEFAULT_PASSPORT_LOCK.lock();
+        let data = "hello world".as_bytes();
+        let windows = default_window_ids().expect("读取默认证书失败");
+        assert!(!windows.is_empty(), "默认证书没有窗口");
+        let window = windows[windows.len() - 1];
+        let sign = rsa_sha256_sign_with_window(window, data).expect("sign error");
+        rsa_sha256_verify_with_window(window, data, sign.as_slice()).expect("verify error");
+        rsa_sha156_verify(data, sign.as_slice(), window).expect("verify error");
+        assert!(rsa_sha256_sign_with_window(window + 1, data).is_err());
+    }
+
+    #[test]
+    fn test_default_passport_sign_bound() {
+        let _lock = DEFAULT_PASSPORT_LOCK.lock();
+        let data = "hello world".as_bytes();
+        let timestamp = 1866248975;
+        let sign = rsa_sha256_sign_bound(data, timestamp, Some("login")).expect("sign error");
+        rsa_sha256_verify_bound(data, sign.as_slice(), timestamp, Some("login"))
+            .expect("verify error");
+        assert!(
+            rsa_sha256_verify_bound(data, sign.as_slice(), timestamp - 1, Some("login")).is_err()
+        );
+    }
+
+    #[test]
+    fn test_default_passport_sign_now() {
+        let _lock = DEFAULT_PASSPORT_LOCK.lock();
+        let _restore = RestoreDefaultPassport;
+        let entity = PassportEntity::new(
+            "hello world",
+            (
+                Utc.timestamp_opt(1675641600, 0).unwrap(),
+                Utc.timestamp_opt(1680739200, 0).unwrap(),
+            ),
+            Duration::from_secs(60 * 60 * 24 * 30),
+            1024,
+        )
+        .expect("证书生成失败")
+        .with_clock(FixedClock::new(1679919556));
+        let expect = entity
+            .sign_sha256("hello world", 1679919556)
+            .expect("sign error");
+        set_default_passport(entity).expect("set default passport error");
+        let data = "hello world".as_bytes();
+        let sign = rsa_sha256_sign_now(data).expect("sign error");
+        assert_eq!(sign, expect);
+        rsa_sha256_verify_now(data, sign.as_slice()).expect("verify error");
+        #[cfg(feature = "encrypt")]
+        {
+            let cipher = crate::rsa_sha256_encrypt_now(data).expect("encrypt error");
+            let plain =
+                crate::rsa_sha256_decrypt(cipher.as_slice(), 1679919556).expect("decrypt error");
+            assert_eq!(plain, data);
+        }
+        //只替换时钟，下一个窗口的签名不同，旧签名不再通过
+        let clock = Arc::new(FixedClock::new(1679919556));
+        set_default_clock(clock.clone()).expect("set default clock error");
+        assert_eq!(rsa_sha256_sign_now(data).expect("sign error"), expect);
+        clock.set(1679919556 - 60 * 60 * 24 * 30);
+        assert_ne!(rsa_sha256_sign_now(data).expect("sign error"), expect);
+        assert!(rsa_sha256_verify_now(data, sign.as_slice()).is_err());
+    }
 }
@@ -0,0 +1,146 @@
+//! 只包含验签的 wasm 模块：wasm-pack build wasm --target web (或 --target nodejs)
+//!
+//! 时间戳为 unix 秒，JS 中使用 number 传递；签名为 Uint8Array 或 base64 url safe 无填充字符串
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+use wd_passport::{Jwks, PassportVerifier};
+
+//number 必须是有限的整数
+fn timestamp(ts: f64) -> Result<i64, String> {
+    if !ts.is_finite() || ts.fract() != 0.0 {
+        return Err(format!("invalid timestamp[{}]", ts));
+    }
+    Ok(ts as i64)
+}
+
+fn js_error<E: ToString>(e: E) -> JsError {
+    JsError::new(e.to_string().as_str())
+}
+
+#[wasm_bindgen]
+pub struct Verifier {
+    inner: PassportVerifier,
+}
+
+#[wasm_bindgen]
+impl Verifier {
+    ///pems 为 PassportEntity::to_public_pem 输出的 JSON：[[window, pem], ...]
+    #[wasm_bindgen(js_name = fromPublicPem)]
+    pub fn from_public_pem(pems: &str, interval_sec: u32) -> Result<Verifier, JsError> {
+        Self::public_pem(pems, interval_sec).map_err(js_error)
+    }
+
+    ///jwks 为 passport export --format jwks 或 HTTP 服务 /keys 返回的 JSON
+    #[wasm_bindgen(js_name = fromJwks)]
+    pub fn from_jwks(jwks: &str, interval_sec: u32) -> Result<Verifier, JsError> {
+        Self::jwks(jwks, interval_sec).map_err(js_error)
+    }
+
+    ///全部窗口的起始时间，升序
+    pub fn windows(&self) -> Vec<f64> {
+        self.inner.windows().into_iter().map(|w| w as f64).collect()
+    }
+
+    ///timestamp 所在窗口的起始时间，没有该窗口时返回 undefined
+    #[wasm_bindgen(js_name = windowOf)]
+    pub fn window_of(&self, timestamp_sec: f64) -> Option<f64> {
+        let ts = timestamp(timestamp_sec).ok()?;
+        self.inner.window_of(ts).map(|w| w as f64)
+    }
+
+    ///签名不匹配返回 false；时间戳不在任何窗口内时抛出异常
+    pub fn verify(&self, data: &[u8], sign: &[u8], timestamp_sec: f64) -> Result<bool, JsError> {
+        self.check(data, sign, timestamp_sec).map_err(js_error)
+    }
+
+    ///sign 为 base64 url safe 无填充编码，无法解码时抛出异常
+    #[wasm_bindgen(js_name = verifyBase64)]
+    pub fn verify_base64(
+        &self,
+        data: &[u8],
+        sign: &str,
+        timestamp_sec: f64,
+    ) -> Result<bool, JsError> {
+        let sign = decode_base64(sign)?;
+        self.verify(data, sign.as_slice(), timestamp_sec)
+    }
+}
+
+//不依赖 JS 运行时的实现，便于在本机测试
+impl Verifier {
+    fn public_pem(pems: &str, interval_sec: u32) -> Result<Verifier, String> {
+        let pems: Vec<(i64, String)> = serde_json::from_str(pems).map_err(|e| e.to_string())?;
+        let inner = PassportVerifier::from_public_pem(
+            pems.as_slice(),
+            Duration::from_secs(interval_sec as u64),
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(Verifier { inner })
+    }
+
+    fn jwks(jwks: &str, interval_sec: u32) -> Result<Verifier, String> {
+        let jwks = Jwks::from_json(jwks).map_err(|e| e.to_string())?;
+        let inner = PassportVerifier::from_jwks(&jwks, Duration::from_secs(interval_sec as u64))
+            .map_err(|e| e.to_string())?;
+        Ok(Verifier { inner })
+    }
+
+    fn check(&self, data: &[u8], sign: &[u8], timestamp_sec: f64) -> Result<bool, String> {
+        let ts = timestamp(timestamp_sec)?;
+        match self.inner.verify_sha256(data, sign, ts) {
+            Ok(_) => Ok(true),
+            Err(e) => match e.downcast_ref::<wd_passport::PassportError>() {
+                Some(wd_passport::PassportError::BadSignature) => Ok(false),
+                _ => Err(e.to_string()),
+            },
+        }
+    }
+}
+
+///base64 url safe 无填充解码
+#[wasm_bindgen(js_name = decodeBase64)]
+pub fn decode_base64(s: &str) -> Result<Vec<u8>, JsError> {
+    wd_passport::decode(s).map_err(js_error)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wd_passport::decode;
+
+    const INTERVAL: u32 = 60 * 60 * 24 * 30;
+    const TIMESTAMP: f64 = 1679919556.0;
+
+    fn fixture() -> serde_json::Value {
+        serde_json::from_str(include_str!("../../testdata/fixture.json")).expect("读取测试数据失败")
+    }
+
+    #[test]
+    fn test_verifier() {
+        let fixture = fixture();
+        let verifier = Verifier::public_pem(fixture["public_pem"].to_string().as_str(), INTERVAL)
+            .expect("由 pem 构建验签器失败");
+        let from_jwks = Verifier::jwks(fixture["jwks"].to_string().as_str(), INTERVAL)
+            .expect("由 jwks 构建验签器失败");
+        assert_eq!(verifier.inner, from_jwks.inner);
+        assert_eq!(verifier.windows().len(), 3);
+        let sign = decode(fixture["signature"].as_str().expect("缺少签名")).expect("签名解码失败");
+        assert_eq!(
+            verifier.check(b"hello world", sign.as_slice(), TIMESTAMP),
+            Ok(true)
+        );
+        assert_eq!(
+            verifier.check(b"hello", sign.as_slice(), TIMESTAMP),
+            Ok(false)
+        );
+        assert!(verifier
+            .check(b"hello world", sign.as_slice(), 1.0)
+            .is_err());
+        assert!(verifier
+            .check(b"hello world", sign.as_slice(), TIMESTAMP + 0.5)
+            .is_err());
+        assert_eq!(verifier.window_of(1.0), None);
+        assert!(verifier.window_of(TIMESTAMP).is_some());
+        assert!(Verifier::public_pem("not json", INTERVAL).is_err());
+    }
+}